@@ -1,24 +1,130 @@
 //! Pre-hashed 64-bit integers.
+//!
+//! # Crate features
+//!
+//! - `alloc` — Provide items that need a heap,
+//!   such as [`H64Pool`] and [`H64HashMap`].
+//!   Without `std`, maps and sets are based on [hashbrown].
+//! - `arbitrary` — Generate [`H64`]s from fuzzer input with [arbitrary].
+//! - `bytemuck` — Cast slices of [`H64`]s to and from bytes with [bytemuck].
+//! - `dashmap` — Provide concurrent maps and sets with [dashmap].
+//! - `global` — Provide `alloc_global`,
+//!   which allocates from an allocator shared by the whole program.
+//! - `hashbrown` — Base [`H64HashMap`] and friends on [hashbrown]
+//!   instead of the standard library, and provide helpers in the `hashbrown` module.
+//! - `heapless` — Provide `H64HeaplessMap` and `H64HeaplessSet`,
+//!   which have a fixed capacity and do not allocate, with [heapless].
+//! - `indexmap` — Provide `H64IndexMap` and `H64IndexSet`,
+//!   which preserve insertion order, with [indexmap].
+//! - `proptest` — Generate [`H64`]s in property tests with [proptest].
+//! - `quickcheck` — Generate [`H64`]s in property tests with [quickcheck].
+//! - `rand` — Sample random [`H64`]s with [rand].
+//! - `rayon` — Parallelize batch conversions such as [`H64::hash_slice`] with [rayon].
+//! - `rkyv` — Archive [`H64`]s zero-copy with [rkyv].
+//! - `roaring` — Provide compressed sets of [`H64`]s with [roaring].
+//! - `serde` — Serialize and deserialize IDs and allocator state with [serde].
+//! - `simd` — Vectorize batch conversions such as [`H64::hash_slice`]
+//!   with [`core::simd`]. This requires a nightly compiler.
+//! - `std` (default) — Provide items that need an operating system,
+//!   such as [`H64SnowflakeGenerator`] and [`H64ShardedMap`].
+//!   This implies `alloc`.
+//! - `zerocopy` — Read [`H64`]s from byte buffers with [zerocopy].
+//!
+//! [arbitrary]: https://docs.rs/arbitrary
+//! [bytemuck]: https://docs.rs/bytemuck
+//! [dashmap]: https://docs.rs/dashmap
+//! [hashbrown]: https://docs.rs/hashbrown
+//! [heapless]: https://docs.rs/heapless
+//! [indexmap]: https://docs.rs/indexmap
+//! [proptest]: https://docs.rs/proptest
+//! [quickcheck]: https://docs.rs/quickcheck
+//! [rand]: https://docs.rs/rand
+//! [rayon]: https://docs.rs/rayon
+//! [rkyv]: https://rkyv.org
+//! [roaring]: https://docs.rs/roaring
+//! [serde]: https://serde.rs
+//! [zerocopy]: https://docs.rs/zerocopy
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::{
+    cmp,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem,
+    num::{IntErrorKind, NonZeroU64},
+    ops::Range,
+    slice,
+    str::FromStr,
+};
+
+#[cfg(feature = "alloc")]
 use {
-    core::{fmt, hash::{BuildHasherDefault, Hasher}},
-    std::collections::{HashMap, HashSet},
+    alloc::{boxed::Box, collections::{BTreeMap, BTreeSet, VecDeque}, string::String, vec, vec::Vec},
+    core::{cell::RefCell, hash::BuildHasherDefault, ops::{Bound, Index, IndexMut, RangeBounds}},
 };
 
+#[cfg(feature = "std")]
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasher, RandomState},
+    sync::{Arc, PoisonError, RwLock},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+#[cfg(all(feature = "std", not(feature = "hashbrown")))]
+use std::collections::{HashMap, HashSet};
+
+#[cfg(all(feature = "alloc", any(feature = "hashbrown", not(feature = "std"))))]
+use ::hashbrown::{HashMap, HashSet};
+
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+use core::sync::atomic::AtomicBool;
+
 /// Pre-hashed 64-bit integer.
 ///
 /// This stores the hash of the [`u64`] it represents,
 /// eliminating the need to compute the hash on every use
 /// (but only when used with [`H64Hasher`]).
 /// Formatting the value reveals the original [`u64`].
+///
+/// [`H64`] is guaranteed to have the same layout as [`u64`],
+/// namely that of its hashed value (see [`into_raw`]).
+///
+/// [`into_raw`]: `Self::into_raw`
 #[derive(Clone, Copy, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "zerocopy", derive(::zerocopy::AsBytes, ::zerocopy::FromBytes, ::zerocopy::FromZeroes))]
+#[repr(transparent)]
 pub struct H64(u64);
 
 impl H64
 {
+    /// The [`H64`] for zero, which is also the [`Default`].
+    pub const ZERO: Self = Self::hash(0);
+
+    /// The [`H64`] for [`u64::MAX`].
+    pub const MAX: Self = Self::hash(u64::MAX);
+
+    /// Sentinel for the absence of a valid [`H64`].
+    ///
+    /// This is [`MAX`](Self::MAX), which [`H64Allocator`], [`AtomicH64Allocator`],
+    /// [`SharedH64Allocator`], and [`LocalH64Allocator`] never allocate.
+    /// Other allocators, such as [`TaggedH64Allocator`], may allocate it.
+    pub const INVALID: Self = Self::MAX;
+
     /// Create a [`H64`] from a [`u64`].
     #[inline]
-    pub fn hash(mut i: u64) -> Self
+    pub const fn hash(mut i: u64) -> Self
     {
         i = u64::wrapping_mul(i ^ i >> 30, 0xBF58476D1CE4E5B9);
         i = u64::wrapping_mul(i ^ i >> 27, 0x94D049BB133111EB);
@@ -28,7 +134,7 @@ impl H64
 
     /// Obtain the original [`u64`].
     #[inline]
-    pub fn unhash(self) -> u64
+    pub const fn unhash(self) -> u64
     {
         let Self(mut i) = self;
         i = u64::wrapping_mul(i ^ i >> 31 ^ i >> 62, 0x319642B2D24D8EC3);
@@ -36,13 +142,370 @@ impl H64
         i =  i ^ i >> 30 ^ i >> 60;
         i
     }
+
+    /// Create a [`H64`] from its hashed value.
+    ///
+    /// This is the inverse of [`into_raw`](Self::into_raw)
+    /// and does not compute the hash.
+    #[inline(always)]
+    pub const fn from_raw(hashed: u64) -> Self
+    {
+        Self(hashed)
+    }
+
+    /// Obtain the hashed value.
+    ///
+    /// This is the value stored in memory and fed to [`H64Hasher`].
+    #[inline(always)]
+    pub const fn into_raw(self) -> u64
+    {
+        self.0
+    }
+
+    /// The [`H64`] for the successor of the original [`u64`].
+    ///
+    /// Returns [`None`] if the original [`u64`] is [`u64::MAX`].
+    #[inline]
+    pub const fn next(self) -> Option<Self>
+    {
+        self.checked_add(1)
+    }
+
+    /// The [`H64`] for the predecessor of the original [`u64`].
+    ///
+    /// Returns [`None`] if the original [`u64`] is zero.
+    #[inline]
+    pub const fn prev(self) -> Option<Self>
+    {
+        self.checked_sub(1)
+    }
+
+    /// Add to the original [`u64`].
+    ///
+    /// Returns [`None`] on overflow.
+    #[inline]
+    pub const fn checked_add(self, n: u64) -> Option<Self>
+    {
+        match self.unhash().checked_add(n) {
+            Some(i) => Some(Self::hash(i)),
+            None => None,
+        }
+    }
+
+    /// Subtract from the original [`u64`].
+    ///
+    /// Returns [`None`] on underflow.
+    #[inline]
+    pub const fn checked_sub(self, n: u64) -> Option<Self>
+    {
+        match self.unhash().checked_sub(n) {
+            Some(i) => Some(Self::hash(i)),
+            None => None,
+        }
+    }
+
+    /// Add to the original [`u64`], wrapping around on overflow.
+    #[inline]
+    pub const fn wrapping_add(self, n: u64) -> Self
+    {
+        Self::hash(self.unhash().wrapping_add(n))
+    }
+
+    /// The shard this [`H64`] belongs to, out of `num_shards` shards.
+    ///
+    /// This implements jump consistent hashing (Lamping and Veach)
+    /// over the hashed value.
+    /// When the number of shards grows from `n` to `n + 1`,
+    /// only about `1 / (n + 1)` of the [`H64`]s move,
+    /// and all of them move to the new shard.
+    ///
+    /// Panics if `num_shards` is zero.
+    pub fn shard(self, num_shards: u32) -> u32
+    {
+        assert!(num_shards > 0, "number of shards must be nonzero");
+        let mut key = self.0;
+        let mut b = 0i64;
+        let mut j = 0i64;
+        while j < i64::from(num_shards) {
+            b = j;
+            key = u64::wrapping_add(u64::wrapping_mul(key, 2862933555777941757), 1);
+            j = ((b + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+        }
+        b as u32
+    }
+
+    /// The bucket this [`H64`] belongs to, out of `n` buckets.
+    ///
+    /// This is the recommended way to bucket [`H64`]s.
+    /// It multiplies the hashed value by `n` and keeps the high bits,
+    /// which is faster than taking the remainder
+    /// and uses the well-mixed high bits of the hashed value.
+    /// Unlike [`shard`](Self::shard), almost all [`H64`]s
+    /// move to another bucket when `n` changes.
+    ///
+    /// Panics if `n` is zero.
+    #[inline]
+    pub const fn partition(self, n: usize) -> usize
+    {
+        assert!(n > 0, "number of buckets must be nonzero");
+        ((self.0 as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Compute the [`partition`](Self::partition) of each [`H64`] in a slice.
+    ///
+    /// Panics if `n` is zero
+    /// or if the slices have different lengths.
+    pub fn partition_slice(h64s: &[Self], n: usize, buckets: &mut [usize])
+    {
+        assert!(n > 0, "number of buckets must be nonzero");
+        assert_eq!(h64s.len(), buckets.len(), "slices have different lengths");
+        for (h64, bucket) in Iterator::zip(h64s.iter(), buckets.iter_mut()) {
+            *bucket = h64.partition(n);
+        }
+    }
+
+    /// Parse the original [`u64`] in decimal, preceded by a prefix.
+    ///
+    /// This is the inverse of formatting the prefix followed by the [`H64`].
+    /// Strings with a different prefix are rejected,
+    /// so that IDs of one type cannot be parsed as another.
+    #[inline]
+    pub fn parse_prefixed(s: &str, prefix: &str) -> Result<Self, H64ParseError>
+    {
+        let s = s.strip_prefix(prefix).ok_or(H64ParseError(H64ParseErrorKind::Prefix))?;
+        Self::from_str(s)
+    }
+
+    /// Encode the original [`u64`] in base58.
+    ///
+    /// This uses the Bitcoin alphabet,
+    /// which omits the lookalike characters `0`, `O`, `I`, and `l`.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn to_base58(self) -> String
+    {
+        encode_digits(self.unhash(), BASE58)
+    }
+
+    /// Decode the original [`u64`] from base58.
+    ///
+    /// This is the inverse of [`to_base58`](Self::to_base58).
+    /// Leading zero digits are rejected, so every ID has one spelling.
+    #[inline]
+    pub fn from_base58(s: &str) -> Result<Self, H64ParseError>
+    {
+        decode_digits(s, BASE58).map(Self::hash)
+    }
+
+    /// Encode the original [`u64`] in base62,
+    /// using digits, then uppercase, then lowercase letters.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn to_base62(self) -> String
+    {
+        encode_digits(self.unhash(), BASE62)
+    }
+
+    /// Decode the original [`u64`] from base62.
+    ///
+    /// This is the inverse of [`to_base62`](Self::to_base62).
+    /// Leading zero digits are rejected, so every ID has one spelling.
+    #[inline]
+    pub fn from_base62(s: &str) -> Result<Self, H64ParseError>
+    {
+        decode_digits(s, BASE62).map(Self::hash)
+    }
+
+    /// Create a [`H64`] from two [`u32`]s.
+    ///
+    /// They are packed into the original [`u64`] before hashing,
+    /// `hi` in the high half and `lo` in the low half.
+    #[inline]
+    pub const fn pack(hi: u32, lo: u32) -> Self
+    {
+        Self::hash((hi as u64) << 32 | lo as u64)
+    }
+
+    /// Obtain the high half of the original [`u64`].
+    ///
+    /// This is the inverse of [`pack`](Self::pack).
+    #[inline]
+    pub const fn hi(self) -> u32
+    {
+        (self.unhash() >> 32) as u32
+    }
+
+    /// Obtain the low half of the original [`u64`].
+    ///
+    /// This is the inverse of [`pack`](Self::pack).
+    #[inline]
+    pub const fn lo(self) -> u32
+    {
+        self.unhash() as u32
+    }
+
+    /// Interpret hashed values as [`H64`]s without copying.
+    ///
+    /// This is [`from_raw`](Self::from_raw) for slices.
+    #[inline]
+    pub fn cast_slice(hashed: &[u64]) -> &[Self]
+    {
+        // SAFETY: H64 is a transparent wrapper of u64.
+        unsafe { slice::from_raw_parts(hashed.as_ptr().cast(), hashed.len()) }
+    }
+
+    /// Interpret hashed values as [`H64`]s without copying.
+    ///
+    /// This is [`from_raw`](Self::from_raw) for mutable slices.
+    #[inline]
+    pub fn cast_slice_mut(hashed: &mut [u64]) -> &mut [Self]
+    {
+        // SAFETY: H64 is a transparent wrapper of u64.
+        unsafe { slice::from_raw_parts_mut(hashed.as_mut_ptr().cast(), hashed.len()) }
+    }
+
+    /// Interpret [`H64`]s as their hashed values without copying.
+    ///
+    /// This is [`into_raw`](Self::into_raw) for slices.
+    #[inline]
+    pub fn raw_slice(h64s: &[Self]) -> &[u64]
+    {
+        // SAFETY: H64 is a transparent wrapper of u64.
+        unsafe { slice::from_raw_parts(h64s.as_ptr().cast(), h64s.len()) }
+    }
+
+    /// Interpret [`H64`]s as their hashed values without copying.
+    ///
+    /// This is [`into_raw`](Self::into_raw) for mutable slices.
+    #[inline]
+    pub fn raw_slice_mut(h64s: &mut [Self]) -> &mut [u64]
+    {
+        // SAFETY: H64 is a transparent wrapper of u64.
+        unsafe { slice::from_raw_parts_mut(h64s.as_mut_ptr().cast(), h64s.len()) }
+    }
+
+    /// Hash each [`u64`] in a slice.
+    #[cfg(feature = "alloc")]
+    pub fn hash_slice(is: &[u64]) -> Vec<Self>
+    {
+        let mut h64s = Self::cast_slice(is).to_vec();
+        Self::hash_slice_in_place(Self::raw_slice_mut(&mut h64s));
+        h64s
+    }
+
+    /// Hash each [`u64`] in a slice, reusing its memory.
+    ///
+    /// Returns the same slice, interpreted as [`H64`]s.
+    pub fn hash_slice_in_place(is: &mut [u64]) -> &mut [Self]
+    {
+        #[cfg(feature = "simd")]
+        let rest = simd::hash_in_place(is);
+        #[cfg(not(feature = "simd"))]
+        let rest = &mut *is;
+        for i in rest {
+            *i = Self::hash(*i).0;
+        }
+        Self::cast_slice_mut(is)
+    }
+
+    /// Obtain the original [`u64`] of each [`H64`] in a slice.
+    #[cfg(feature = "alloc")]
+    pub fn unhash_slice(h64s: &[Self]) -> Vec<u64>
+    {
+        let mut is = Self::raw_slice(h64s).to_vec();
+        Self::unhash_slice_in_place(Self::cast_slice_mut(&mut is));
+        is
+    }
+
+    /// Obtain the original [`u64`] of each [`H64`] in a slice, reusing its memory.
+    ///
+    /// Returns the same slice, interpreted as [`u64`]s.
+    pub fn unhash_slice_in_place(h64s: &mut [Self]) -> &mut [u64]
+    {
+        let is = Self::raw_slice_mut(h64s);
+        #[cfg(feature = "simd")]
+        let rest = simd::unhash_in_place(is);
+        #[cfg(not(feature = "simd"))]
+        let rest = &mut *is;
+        for i in rest {
+            *i = Self(*i).unhash();
+        }
+        is
+    }
+}
+
+#[cfg(feature = "simd")]
+mod simd
+{
+    //! Vectorized batch conversions.
+    //!
+    //! These compute the same functions as [`H64::hash`] and [`H64::unhash`],
+    //! on eight lanes at a time.
+
+    use core::simd::{Simd, u64x8};
+
+    /// Hash all whole chunks of lanes in place.
+    ///
+    /// Returns the remaining elements, which must be hashed separately.
+    pub fn hash_in_place(is: &mut [u64]) -> &mut [u64]
+    {
+        let mut chunks = is.chunks_exact_mut(u64x8::LEN);
+        for chunk in &mut chunks {
+            let mut i = u64x8::from_slice(chunk);
+            i = (i ^ i >> s(30)) * s(0xBF58476D1CE4E5B9);
+            i = (i ^ i >> s(27)) * s(0x94D049BB133111EB);
+            i =  i ^ i >> s(31);
+            i.copy_to_slice(chunk);
+        }
+        chunks.into_remainder()
+    }
+
+    /// Unhash all whole chunks of lanes in place.
+    ///
+    /// Returns the remaining elements, which must be unhashed separately.
+    pub fn unhash_in_place(is: &mut [u64]) -> &mut [u64]
+    {
+        let mut chunks = is.chunks_exact_mut(u64x8::LEN);
+        for chunk in &mut chunks {
+            let mut i = u64x8::from_slice(chunk);
+            i = (i ^ i >> s(31) ^ i >> s(62)) * s(0x319642B2D24D8EC3);
+            i = (i ^ i >> s(27) ^ i >> s(54)) * s(0x96DE1B173F119089);
+            i =  i ^ i >> s(30) ^ i >> s(60);
+            i.copy_to_slice(chunk);
+        }
+        chunks.into_remainder()
+    }
+
+    #[inline(always)]
+    fn s(i: u64) -> u64x8
+    {
+        Simd::splat(i)
+    }
+}
+
+impl Default for H64
+{
+    #[inline(always)]
+    fn default() -> Self
+    {
+        Self::ZERO
+    }
 }
 
+/// Formats the original [`u64`].
+/// The alternate form `{:#?}` also shows the hashed value.
 impl fmt::Debug for H64
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
     {
-        self.unhash().fmt(f)
+        if f.alternate() {
+            f.debug_struct("H64")
+                .field("value", &self.unhash())
+                .field("hashed", &format_args!("{:#018x}", self.0))
+                .finish()
+        } else {
+            self.unhash().fmt(f)
+        }
     }
 }
 
@@ -54,90 +517,10299 @@ impl fmt::Display for H64
     }
 }
 
-/// Utility for generating [`H64`]s.
-pub struct H64Allocator(u64);
+impl fmt::Binary for H64
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        fmt::Binary::fmt(&self.unhash(), f)
+    }
+}
 
-impl H64Allocator
+impl fmt::LowerHex for H64
 {
-    /// Create a new allocator.
-    #[inline(always)]
-    pub fn new() -> Self
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
     {
-        Self(0)
+        fmt::LowerHex::fmt(&self.unhash(), f)
     }
+}
+
+impl fmt::Octal for H64
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        fmt::Octal::fmt(&self.unhash(), f)
+    }
+}
+
+impl fmt::UpperHex for H64
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        fmt::UpperHex::fmt(&self.unhash(), f)
+    }
+}
+
+/// Parse the original [`u64`] in decimal, as formatted by [`Display`](fmt::Display).
+impl FromStr for H64
+{
+    type Err = H64ParseError;
 
-    /// Allocate the next [`H64`].
     #[inline]
-    pub fn alloc(&mut self) -> H64
+    fn from_str(s: &str) -> Result<Self, H64ParseError>
     {
-        let h64 = H64::hash(self.0);
-        self.0 += 1;
-        h64
+        parse_u64(s).map(Self::hash)
     }
 }
 
-/// Identity hasher for use with [`H64`].
-///
-/// This hasher only implements [`write_u64`].
-/// The implementation is the identity function.
-/// Its use improves hashing performance by ∞%.
+/// [`H64`] ordered by its original [`u64`].
 ///
-/// [`write_u64`]: `Self::write_u64`
-#[derive(Default)]
-pub struct H64Hasher(u64);
+/// [`H64`] does not implement [`Ord`],
+/// because there are two sensible orders.
+/// This one is meaningful to humans, but unhashes on every comparison.
+/// See also [`OrdByHash`].
+/// It is used as the key of [`H64BTreeMap`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct OrdByValue(pub H64);
 
-impl Hasher for H64Hasher
+impl Ord for OrdByValue
 {
-    #[inline(always)]
-    fn finish(&self) -> u64
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering
     {
-        self.0
+        Ord::cmp(&self.0.unhash(), &other.0.unhash())
+    }
+}
+
+impl PartialOrd for OrdByValue
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering>
+    {
+        Some(self.cmp(other))
     }
+}
 
-    fn write(&mut self, _bytes: &[u8])
+impl From<H64> for OrdByValue
+{
+    #[inline(always)]
+    fn from(other: H64) -> Self
     {
-        unimplemented!("only use with H64")
+        Self(other)
     }
+}
 
+impl From<OrdByValue> for H64
+{
     #[inline(always)]
-    fn write_u64(&mut self, i: u64)
+    fn from(other: OrdByValue) -> Self
     {
-        self.0 = i;
+        other.0
     }
 }
 
-/// Specialization of [`HashMap`] that uses [`H64Hasher`].
-pub type H64HashMap<K, V> = HashMap<K, V, BuildHasherDefault<H64Hasher>>;
+impl fmt::Display for OrdByValue
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
 
-/// Specialization of [`HashSet`] that uses [`H64Hasher`].
-pub type H64HashSet<T> = HashSet<T, BuildHasherDefault<H64Hasher>>;
+/// [`H64`] ordered by its hashed value.
+///
+/// This order is arbitrary but stable, and comparisons are free.
+/// Use it where any total order will do, such as deduplication.
+/// See also [`OrdByValue`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct OrdByHash(pub H64);
 
-#[cfg(test)]
-mod tests
+impl Ord for OrdByHash
 {
-    use {super::*, core::hash::Hash};
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering
+    {
+        Ord::cmp(&self.0.into_raw(), &other.0.into_raw())
+    }
+}
 
-    fn test_values() -> impl Iterator<Item = u64>
+impl PartialOrd for OrdByHash
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering>
     {
-        Iterator::chain(0 .. 1000, u64::MAX - 1000 .. u64::MAX)
+        Some(self.cmp(other))
     }
+}
 
-    #[test]
-    fn unhash_undos_hash()
+impl From<H64> for OrdByHash
+{
+    #[inline(always)]
+    fn from(other: H64) -> Self
     {
-        for i in test_values() {
-            assert_eq!(H64::hash(i).unhash(), i);
-        }
+        Self(other)
     }
+}
 
-    #[test]
-    fn hasher_does_nothing()
+impl From<OrdByHash> for H64
+{
+    #[inline(always)]
+    fn from(other: OrdByHash) -> Self
     {
-        for i in test_values() {
-            let h64 = H64::hash(i);
-            let mut hasher = H64Hasher::default();
-            Hash::hash(&h64, &mut hasher);
-            assert_eq!(hasher.finish(), h64.0);
+        other.0
+    }
+}
+
+impl fmt::Display for OrdByHash
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+/// [`H64`] that is formatted without revealing its original [`u64`].
+///
+/// [`Display`](fmt::Display) and [`Debug`](fmt::Debug) print a stable token,
+/// derived from the 32 high bits of a second hash.
+/// Many [`u64`]s share a token, so it cannot be reversed;
+/// but note that IDs from a small range can still be found by brute force.
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct Redacted(pub H64);
+
+impl Redacted
+{
+    /// The token that is printed instead of the original [`u64`].
+    #[inline]
+    pub const fn token(self) -> u32
+    {
+        (H64::hash(self.0.0).0 >> 32) as u32
+    }
+}
+
+impl From<H64> for Redacted
+{
+    #[inline(always)]
+    fn from(other: H64) -> Self
+    {
+        Self(other)
+    }
+}
+
+impl fmt::Debug for Redacted
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "Redacted({self})")
+    }
+}
+
+impl fmt::Display for Redacted
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "redacted-{:08x}", self.token())
+    }
+}
+
+/// [`H64`] whose original [`u64`] includes a checksum.
+///
+/// The low [`CHECK_BITS`] bits of the original [`u64`]
+/// are derived from the remaining bits, which hold the value.
+/// Parsing rejects all but about one in 256 corrupted IDs,
+/// such as those with a mistyped digit.
+///
+/// [`CHECK_BITS`]: `Self::CHECK_BITS`
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct H64Checked(H64);
+
+impl H64Checked
+{
+    /// The number of bits taken up by the checksum.
+    pub const CHECK_BITS: u32 = 8;
+
+    /// The largest value.
+    pub const VALUE_MAX: u64 = u64::MAX >> Self::CHECK_BITS;
+
+    /// Create a [`H64Checked`] from a value.
+    ///
+    /// Panics if the value exceeds [`VALUE_MAX`](Self::VALUE_MAX).
+    #[inline]
+    pub const fn new(value: u64) -> Self
+    {
+        match Self::try_new(value) {
+            Some(checked) => checked,
+            None => panic!("H64Checked value out of range"),
+        }
+    }
+
+    /// Create a [`H64Checked`] from a value, unless it is out of range.
+    #[inline]
+    pub const fn try_new(value: u64) -> Option<Self>
+    {
+        if value > Self::VALUE_MAX {
+            return None;
+        }
+        Some(Self(H64::hash(value << Self::CHECK_BITS | Self::checksum(value))))
+    }
+
+    /// Verify the checksum of a [`H64`].
+    #[inline]
+    pub const fn from_h64(h64: H64) -> Option<Self>
+    {
+        let i = h64.unhash();
+        let value = i >> Self::CHECK_BITS;
+        if i & !(u64::MAX << Self::CHECK_BITS) != Self::checksum(value) {
+            return None;
+        }
+        Some(Self(h64))
+    }
+
+    /// Obtain the value.
+    #[inline]
+    pub const fn value(self) -> u64
+    {
+        self.0.unhash() >> Self::CHECK_BITS
+    }
+
+    /// Convert the [`H64Checked`] to a [`H64`].
+    #[inline(always)]
+    pub const fn get(self) -> H64
+    {
+        self.0
+    }
+
+    #[inline]
+    const fn checksum(value: u64) -> u64
+    {
+        H64::hash(value).0 >> (64 - Self::CHECK_BITS)
+    }
+}
+
+impl From<H64Checked> for H64
+{
+    #[inline(always)]
+    fn from(other: H64Checked) -> Self
+    {
+        other.get()
+    }
+}
+
+/// Parse the original [`u64`] in decimal and verify the checksum.
+impl FromStr for H64Checked
+{
+    type Err = H64ParseError;
+
+    fn from_str(s: &str) -> Result<Self, H64ParseError>
+    {
+        let h64 = H64::from_str(s)?;
+        Self::from_h64(h64).ok_or(H64ParseError(H64ParseErrorKind::Checksum))
+    }
+}
+
+impl fmt::Debug for H64Checked
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for H64Checked
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+/// Parse a decimal [`u64`].
+fn parse_u64(s: &str) -> Result<u64, H64ParseError>
+{
+    u64::from_str(s).map_err(|err| {
+        H64ParseError(match err.kind() {
+            IntErrorKind::Empty => H64ParseErrorKind::Empty,
+            IntErrorKind::PosOverflow => H64ParseErrorKind::Overflow,
+            _ => H64ParseErrorKind::InvalidDigit,
+        })
+    })
+}
+
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+const BASE62: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Encode a [`u64`] with the given digits, most significant first.
+#[cfg(feature = "alloc")]
+fn encode_digits(mut i: u64, digits: &[u8]) -> String
+{
+    let base = digits.len() as u64;
+    let mut buffer = [0; 64];
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        buffer[start] = digits[(i % base) as usize];
+        i /= base;
+        if i == 0 {
+            break;
+        }
+    }
+    buffer[start ..].iter().map(|&b| b as char).collect()
+}
+
+/// Decode a [`u64`] with the given digits, most significant first.
+///
+/// Leading zero digits are rejected.
+fn decode_digits(s: &str, digits: &[u8]) -> Result<u64, H64ParseError>
+{
+    if s.is_empty() {
+        return Err(H64ParseError(H64ParseErrorKind::Empty));
+    }
+    if s.len() > 1 && s.as_bytes()[0] == digits[0] {
+        return Err(H64ParseError(H64ParseErrorKind::InvalidDigit));
+    }
+    let base = digits.len() as u64;
+    s.bytes().try_fold(0u64, |i, b| {
+        let digit = digits.iter().position(|&d| d == b)
+            .ok_or(H64ParseError(H64ParseErrorKind::InvalidDigit))?;
+        u64::checked_mul(i, base)
+            .and_then(|i| i.checked_add(digit as u64))
+            .ok_or(H64ParseError(H64ParseErrorKind::Overflow))
+    })
+}
+
+/// Error returned when parsing an ID fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct H64ParseError(H64ParseErrorKind);
+
+impl H64ParseError
+{
+    /// Why parsing failed.
+    #[inline(always)]
+    pub const fn kind(&self) -> H64ParseErrorKind
+    {
+        self.0
+    }
+}
+
+impl fmt::Display for H64ParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let reason = match self.0 {
+            H64ParseErrorKind::Empty => "empty string",
+            H64ParseErrorKind::InvalidDigit => "invalid digit",
+            H64ParseErrorKind::Overflow => "number too large",
+            H64ParseErrorKind::Checksum => "checksum mismatch",
+            H64ParseErrorKind::Prefix => "missing or wrong prefix",
+        };
+        write!(f, "cannot parse ID: {reason}")
+    }
+}
+
+impl core::error::Error for H64ParseError
+{
+}
+
+/// Reason for a [`H64ParseError`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum H64ParseErrorKind
+{
+    /// The string is empty.
+    Empty,
+
+    /// The string contains a character that is not a digit.
+    InvalidDigit,
+
+    /// The number does not fit in a [`u64`].
+    Overflow,
+
+    /// The checksum does not match the value.
+    Checksum,
+
+    /// The string does not start with the expected prefix.
+    Prefix,
+}
+
+/// Pre-hashed nonzero 64-bit integer.
+///
+/// Like [`H64`], but for [`NonZeroU64`]s.
+/// The hash maps zero to zero, so nonzero integers have nonzero hashes,
+/// and `Option<NonZeroH64>` is no larger than [`H64`].
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct NonZeroH64(NonZeroU64);
+
+impl NonZeroH64
+{
+    /// Create a [`NonZeroH64`] from a [`NonZeroU64`].
+    #[inline]
+    pub const fn hash(i: NonZeroU64) -> Self
+    {
+        let H64(i) = H64::hash(i.get());
+        // SAFETY: The hash is a bijection that maps zero to zero.
+        Self(unsafe { NonZeroU64::new_unchecked(i) })
+    }
+
+    /// Obtain the original [`NonZeroU64`].
+    #[inline]
+    pub const fn unhash(self) -> NonZeroU64
+    {
+        let i = self.get().unhash();
+        // SAFETY: The unhash is a bijection that maps zero to zero.
+        unsafe { NonZeroU64::new_unchecked(i) }
+    }
+
+    /// Create a [`NonZeroH64`] from a [`H64`].
+    ///
+    /// Returns [`None`] if the [`H64`] represents zero.
+    #[inline]
+    pub const fn new(h64: H64) -> Option<Self>
+    {
+        match NonZeroU64::new(h64.0) {
+            Some(i) => Some(Self(i)),
+            None => None,
+        }
+    }
+
+    /// Convert the [`NonZeroH64`] to a [`H64`].
+    #[inline]
+    pub const fn get(self) -> H64
+    {
+        H64(self.0.get())
+    }
+}
+
+impl From<NonZeroH64> for H64
+{
+    #[inline]
+    fn from(other: NonZeroH64) -> Self
+    {
+        other.get()
+    }
+}
+
+impl fmt::Debug for NonZeroH64
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.unhash().fmt(f)
+    }
+}
+
+impl fmt::Display for NonZeroH64
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.unhash().fmt(f)
+    }
+}
+
+/// [`H64`] tagged with a type.
+///
+/// IDs with different tags are different types,
+/// so they cannot be compared or mixed up.
+/// The tag is only used at compile time and need not implement any traits.
+#[repr(transparent)]
+pub struct H64Id<T: ?Sized>(H64, PhantomData<fn() -> T>);
+
+impl<T: ?Sized> H64Id<T>
+{
+    /// Tag a [`H64`].
+    #[inline(always)]
+    pub const fn new(h64: H64) -> Self
+    {
+        Self(h64, PhantomData)
+    }
+
+    /// Create a [`H64Id`] from a [`u64`].
+    #[inline]
+    pub const fn hash(i: u64) -> Self
+    {
+        Self::new(H64::hash(i))
+    }
+
+    /// Obtain the original [`u64`].
+    #[inline]
+    pub const fn unhash(self) -> u64
+    {
+        self.0.unhash()
+    }
+
+    /// Remove the tag.
+    #[inline(always)]
+    pub const fn get(self) -> H64
+    {
+        self.0
+    }
+}
+
+impl<T: ?Sized> From<H64Id<T>> for H64
+{
+    #[inline(always)]
+    fn from(other: H64Id<T>) -> Self
+    {
+        other.get()
+    }
+}
+
+impl<T: ?Sized> Clone for H64Id<T>
+{
+    #[inline(always)]
+    fn clone(&self) -> Self
+    {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for H64Id<T>
+{
+}
+
+impl<T: ?Sized> Eq for H64Id<T>
+{
+}
+
+impl<T: ?Sized> PartialEq for H64Id<T>
+{
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool
+    {
+        self.0 == other.0
+    }
+}
+
+impl<T: ?Sized> Hash for H64Id<T>
+{
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.0.hash(state);
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for H64Id<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: ?Sized> fmt::Display for H64Id<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+/// Key for [`H64Keyed`].
+///
+/// Each key selects a different permutation of the [`u64`]s.
+/// The permutation is a Feistel network keyed with round keys derived from the key.
+/// This makes IDs unpredictable in practice, but it is not a vetted cipher.
+#[derive(Clone)]
+pub struct H64Key([u64; 6]);
+
+impl H64Key
+{
+    /// Derive the round keys from a key.
+    pub const fn new(key: u64) -> Self
+    {
+        let mut state = key;
+        let mut round_keys = [0; 6];
+        let mut r = 0;
+        while r < round_keys.len() {
+            state = state.wrapping_add(0x9E3779B97F4A7C15);
+            round_keys[r] = H64::hash(state).0;
+            r += 1;
+        }
+        Self(round_keys)
+    }
+
+    #[inline(always)]
+    const fn round(round_key: u64, half: u32) -> u32
+    {
+        (H64::hash(half as u64 ^ round_key).0 >> 32) as u32
+    }
+}
+
+/// Pre-hashed 64-bit integer, hashed with a key.
+///
+/// Unlike [`H64`], which anybody can unhash,
+/// this can only be unhashed with the [`H64Key`] it was hashed with.
+/// Use it for IDs exposed to untrusted parties.
+/// Formatting the value reveals the hashed [`u64`], not the original.
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct H64Keyed(u64);
+
+impl H64Keyed
+{
+    /// Create a [`H64Keyed`] from a [`u64`].
+    pub const fn hash_with(key: &H64Key, i: u64) -> Self
+    {
+        let (mut l, mut r) = ((i >> 32) as u32, i as u32);
+        let mut k = 0;
+        while k < key.0.len() {
+            (l, r) = (r, l ^ H64Key::round(key.0[k], r));
+            k += 1;
+        }
+        Self((l as u64) << 32 | r as u64)
+    }
+
+    /// Obtain the original [`u64`].
+    ///
+    /// The key must be the one used to create the [`H64Keyed`].
+    pub const fn unhash_with(self, key: &H64Key) -> u64
+    {
+        let (mut l, mut r) = ((self.0 >> 32) as u32, self.0 as u32);
+        let mut k = key.0.len();
+        while k > 0 {
+            k -= 1;
+            (l, r) = (r ^ H64Key::round(key.0[k], l), l);
+        }
+        (l as u64) << 32 | r as u64
+    }
+
+    /// Create a [`H64Keyed`] from its hashed [`u64`].
+    #[inline(always)]
+    pub const fn from_hashed(i: u64) -> Self
+    {
+        Self(i)
+    }
+
+    /// Obtain the hashed [`u64`].
+    #[inline(always)]
+    pub const fn hashed(self) -> u64
+    {
+        self.0
+    }
+}
+
+impl fmt::Debug for H64Keyed
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for H64Keyed
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+/// Invertible mix function for use with [`H64Custom`].
+pub trait Bijection
+{
+    /// Mix a [`u64`].
+    fn forward(i: u64) -> u64;
+
+    /// Undo [`forward`].
+    ///
+    /// [`forward`]: `Self::forward`
+    fn inverse(i: u64) -> u64;
+}
+
+/// The mix function used by [`H64`].
+///
+/// This is the finalizer of SplitMix64.
+pub struct SplitMix;
+
+impl Bijection for SplitMix
+{
+    #[inline]
+    fn forward(i: u64) -> u64
+    {
+        H64::hash(i).0
+    }
+
+    #[inline]
+    fn inverse(i: u64) -> u64
+    {
+        H64(i).unhash()
+    }
+}
+
+/// The finalizer of MurmurHash3 (`fmix64`).
+pub struct Murmur3;
+
+impl Bijection for Murmur3
+{
+    #[inline]
+    fn forward(mut i: u64) -> u64
+    {
+        i = u64::wrapping_mul(i ^ i >> 33, 0xFF51AFD7ED558CCD);
+        i = u64::wrapping_mul(i ^ i >> 33, 0xC4CEB9FE1A85EC53);
+        i =  i ^ i >> 33;
+        i
+    }
+
+    #[inline]
+    fn inverse(mut i: u64) -> u64
+    {
+        i = u64::wrapping_mul(i ^ i >> 33, 0x9CB4B2F8129337DB);
+        i = u64::wrapping_mul(i ^ i >> 33, 0x4F74430C22A54005);
+        i =  i ^ i >> 33;
+        i
+    }
+}
+
+/// The avalanche step of XXH3.
+pub struct Xxh3;
+
+impl Bijection for Xxh3
+{
+    #[inline]
+    fn forward(mut i: u64) -> u64
+    {
+        i = u64::wrapping_mul(i ^ i >> 37, 0x165667919E3779F9);
+        i =  i ^ i >> 32;
+        i
+    }
+
+    #[inline]
+    fn inverse(mut i: u64) -> u64
+    {
+        i = u64::wrapping_mul(i ^ i >> 32, 0x08DA8EE41D6DF849);
+        i =  i ^ i >> 37;
+        i
+    }
+}
+
+/// Bijection with custom constants.
+///
+/// This has the same structure as [`SplitMix`]:
+/// three xorshifts by `S1`, `S2`, and `S3`,
+/// separated by multiplications by `M1` and `M2`.
+/// The inverse multipliers are computed at compile time
+/// with [`inverse_multiplier`],
+/// so even multipliers or shifts outside `1 .. 64` fail to build.
+pub struct XorShiftMultiply<
+    const S1: u32, const M1: u64,
+    const S2: u32, const M2: u64,
+    const S3: u32,
+>;
+
+impl<const S1: u32, const M1: u64, const S2: u32, const M2: u64, const S3: u32>
+    XorShiftMultiply<S1, M1, S2, M2, S3>
+{
+    const INVERSE_M1: u64 = inverse_multiplier(M1);
+    const INVERSE_M2: u64 = inverse_multiplier(M2);
+
+    const VALID_SHIFTS: () = assert!(
+        0 < S1 && S1 < 64 && 0 < S2 && S2 < 64 && 0 < S3 && S3 < 64,
+        "shifts must be in 1 .. 64",
+    );
+}
+
+impl<const S1: u32, const M1: u64, const S2: u32, const M2: u64, const S3: u32>
+    Bijection for XorShiftMultiply<S1, M1, S2, M2, S3>
+{
+    #[inline]
+    fn forward(mut i: u64) -> u64
+    {
+        let () = Self::VALID_SHIFTS;
+        let _ = (Self::INVERSE_M1, Self::INVERSE_M2);
+        i = u64::wrapping_mul(i ^ i >> S1, M1);
+        i = u64::wrapping_mul(i ^ i >> S2, M2);
+        i =  i ^ i >> S3;
+        i
+    }
+
+    #[inline]
+    fn inverse(mut i: u64) -> u64
+    {
+        let () = Self::VALID_SHIFTS;
+        i = u64::wrapping_mul(inverse_xorshift(i, S3), Self::INVERSE_M2);
+        i = u64::wrapping_mul(inverse_xorshift(i, S2), Self::INVERSE_M1);
+        i =  inverse_xorshift(i, S1);
+        i
+    }
+}
+
+/// Compute the inverse of a multiplier modulo 2<sup>64</sup>.
+///
+/// Multiplication by an odd number is a bijection,
+/// and multiplication by the result undoes it.
+/// Panics if the multiplier is even,
+/// which in a const context is a compile error.
+pub const fn inverse_multiplier(m: u64) -> u64
+{
+    assert!(m % 2 == 1, "multiplier must be odd");
+
+    // Every odd number is its own inverse modulo 8.
+    // Each Newton step doubles the number of correct bits: 3, 6, …, 96.
+    let mut x = m;
+    let mut k = 0;
+    while k < 5 {
+        x = u64::wrapping_mul(x, u64::wrapping_sub(2, u64::wrapping_mul(m, x)));
+        k += 1;
+    }
+    x
+}
+
+/// Undo `i ^ i >> s`.
+const fn inverse_xorshift(i: u64, s: u32) -> u64
+{
+    let mut r = i;
+    let mut t = s;
+    while t < 64 {
+        r ^= i >> t;
+        t += s;
+    }
+    r
+}
+
+/// Pre-hashed 64-bit integer, hashed with a custom [`Bijection`].
+///
+/// Like [`H64`], but with a different mix function.
+/// This hashes like a [`u64`], so use it with [`H64Hasher`],
+/// for example as the key of a [`H64HashMap`].
+pub struct H64Custom<B: ?Sized>(u64, PhantomData<fn() -> B>);
+
+impl<B: Bijection + ?Sized> H64Custom<B>
+{
+    /// Create a [`H64Custom`] from a [`u64`].
+    #[inline]
+    pub fn hash(i: u64) -> Self
+    {
+        Self(B::forward(i), PhantomData)
+    }
+
+    /// Obtain the original [`u64`].
+    #[inline]
+    pub fn unhash(self) -> u64
+    {
+        B::inverse(self.0)
+    }
+}
+
+impl<B: ?Sized> Clone for H64Custom<B>
+{
+    #[inline(always)]
+    fn clone(&self) -> Self
+    {
+        *self
+    }
+}
+
+impl<B: ?Sized> Copy for H64Custom<B>
+{
+}
+
+impl<B: ?Sized> Eq for H64Custom<B>
+{
+}
+
+impl<B: ?Sized> PartialEq for H64Custom<B>
+{
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool
+    {
+        self.0 == other.0
+    }
+}
+
+impl<B: ?Sized> Hash for H64Custom<B>
+{
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        state.write_u64(self.0);
+    }
+}
+
+impl<B: Bijection + ?Sized> fmt::Debug for H64Custom<B>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.unhash().fmt(f)
+    }
+}
+
+impl<B: Bijection + ?Sized> fmt::Display for H64Custom<B>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.unhash().fmt(f)
+    }
+}
+
+/// Pre-hashed 32-bit integer.
+///
+/// Like [`H64`], but for [`u32`]s.
+/// Use with [`H32Hasher`].
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct H32(u32);
+
+impl H32
+{
+    /// Create a [`H32`] from a [`u32`].
+    #[inline]
+    pub const fn hash(mut i: u32) -> Self
+    {
+        i = u32::wrapping_mul(i ^ i >> 16, 0x7FEB352D);
+        i = u32::wrapping_mul(i ^ i >> 15, 0x846CA68B);
+        i =  i ^ i >> 16;
+        Self(i)
+    }
+
+    /// Obtain the original [`u32`].
+    #[inline]
+    pub const fn unhash(self) -> u32
+    {
+        let Self(mut i) = self;
+        i = u32::wrapping_mul(i ^ i >> 16, 0x43021123);
+        i = u32::wrapping_mul(i ^ i >> 15 ^ i >> 30, 0x1D69E2A5);
+        i =  i ^ i >> 16;
+        i
+    }
+}
+
+impl fmt::Debug for H32
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.unhash().fmt(f)
+    }
+}
+
+impl fmt::Display for H32
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.unhash().fmt(f)
+    }
+}
+
+/// Pre-hashed 128-bit integer.
+///
+/// Like [`H64`], but for [`u128`]s.
+/// Use with [`H128Hasher`].
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct H128(u128);
+
+impl H128
+{
+    /// Create a [`H128`] from a [`u128`].
+    #[inline]
+    pub const fn hash(mut i: u128) -> Self
+    {
+        i = u128::wrapping_mul(i ^ i >> 65, 0x9E3779B97F4A7C15F39CC0605CEDC835);
+        i = u128::wrapping_mul(i ^ i >> 61, 0xD6E8FEB86659FD93A8DC90E2F6F3F8A5);
+        i =  i ^ i >> 64;
+        Self(i)
+    }
+
+    /// Obtain the original [`u128`].
+    #[inline]
+    pub const fn unhash(self) -> u128
+    {
+        let Self(mut i) = self;
+        i = u128::wrapping_mul(i ^ i >> 64, 0x95D90273D446A18EABDBE131C9EB2F2D);
+        i = u128::wrapping_mul(i ^ i >> 61 ^ i >> 122, 0x28969101C22823532F07EB1A988D4A1D);
+        i =  i ^ i >> 65;
+        i
+    }
+}
+
+impl fmt::Debug for H128
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.unhash().fmt(f)
+    }
+}
+
+impl fmt::Display for H128
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.unhash().fmt(f)
+    }
+}
+
+/// Utility for generating [`H64`]s.
+///
+/// The allocator hashes consecutive [`u64`]s.
+/// It is exhausted once it reaches [`u64::MAX`],
+/// which is never allocated.
+#[derive(Default)]
+pub struct H64Allocator(u64);
+
+impl H64Allocator
+{
+    /// Create a new allocator.
+    #[inline(always)]
+    pub const fn new() -> Self
+    {
+        Self(0)
+    }
+
+    /// Create a new allocator whose first [`H64`] represents `offset`.
+    #[inline(always)]
+    pub const fn starting_at(offset: u64) -> Self
+    {
+        Self(offset)
+    }
+
+    /// Create an allocator that continues where another left off.
+    ///
+    /// `position` must be the [`position`] of the other allocator.
+    /// The new allocator then allocates exactly what the other would have.
+    /// This is the same as [`starting_at`],
+    /// but makes checkpointing code read better.
+    ///
+    /// [`position`]: `Self::position`
+    /// [`starting_at`]: `Self::starting_at`
+    #[inline(always)]
+    pub const fn resume(position: u64) -> Self
+    {
+        Self::starting_at(position)
+    }
+
+    /// The original [`u64`] of the next [`H64`] to be allocated.
+    ///
+    /// This is [`u64::MAX`] if the allocator is exhausted.
+    #[inline(always)]
+    pub const fn position(&self) -> u64
+    {
+        self.0
+    }
+
+    /// Reserve a range of original [`u64`]s, so that they are never allocated.
+    ///
+    /// This is useful for sentinels, such as "nil" or "system".
+    /// The allocator skips ahead to the end of the range,
+    /// so any [`u64`]s between the position and the range are skipped too;
+    /// typically, reserve `0 .. N` before allocating anything.
+    /// Empty ranges reserve nothing.
+    /// In debug builds, panics if part of the range may already have been allocated.
+    #[inline]
+    pub const fn reserve_range(&mut self, range: Range<u64>)
+    {
+        debug_assert!(
+            range.start >= range.end || range.start >= self.0,
+            "reserved range overlaps allocated H64s",
+        );
+        if range.start < range.end && range.end > self.0 {
+            self.0 = range.end;
+        }
+    }
+
+    /// Whether the allocator has run out of [`H64`]s.
+    #[inline(always)]
+    pub const fn is_exhausted(&self) -> bool
+    {
+        self.0 == u64::MAX
+    }
+
+    /// Allocate the next [`H64`].
+    ///
+    /// Panics if the allocator is exhausted.
+    #[inline]
+    pub const fn alloc(&mut self) -> H64
+    {
+        self.try_alloc().expect("H64Allocator exhausted")
+    }
+
+    /// Allocate the next [`H64`], unless the allocator is exhausted.
+    #[inline]
+    pub const fn try_alloc(&mut self) -> Option<H64>
+    {
+        if self.is_exhausted() {
+            return None;
+        }
+        let h64 = H64::hash(self.0);
+        self.0 += 1;
+        Some(h64)
+    }
+
+    /// Allocate the next `n` [`H64`]s.
+    ///
+    /// The counter is advanced only once,
+    /// and the [`H64`]s are hashed lazily as the range is iterated.
+    /// Panics if fewer than `n` [`H64`]s are left.
+    #[inline]
+    pub const fn alloc_range(&mut self, n: u64) -> H64Range
+    {
+        self.try_alloc_range(n).expect("H64Allocator exhausted")
+    }
+
+    /// Allocate the next `n` [`H64`]s, unless fewer are left.
+    #[inline]
+    pub const fn try_alloc_range(&mut self, n: u64) -> Option<H64Range>
+    {
+        let start = self.0;
+        match start.checked_add(n) {
+            Some(end) => {
+                self.0 = end;
+                Some(H64Range(start .. end))
+            },
+            None => None,
+        }
+    }
+}
+
+/// The allocator is an iterator that allocates until it is exhausted.
+impl Iterator for H64Allocator
+{
+    type Item = H64;
+
+    #[inline]
+    fn next(&mut self) -> Option<H64>
+    {
+        self.try_alloc()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>)
+    {
+        let remaining = u64::MAX - self.0;
+        (usize::try_from(remaining).unwrap_or(usize::MAX), usize::try_from(remaining).ok())
+    }
+}
+
+impl FusedIterator for H64Allocator
+{
+}
+
+/// Iterator over the [`H64`]s representing a range of [`u64`]s.
+#[derive(Clone, Debug)]
+pub struct H64Range(Range<u64>);
+
+impl Iterator for H64Range
+{
+    type Item = H64;
+
+    #[inline]
+    fn next(&mut self) -> Option<H64>
+    {
+        self.0.next().map(H64::hash)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>)
+    {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for H64Range
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<H64>
+    {
+        self.0.next_back().map(H64::hash)
+    }
+}
+
+impl FusedIterator for H64Range
+{
+}
+
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for H64Range
+{
+}
+
+impl From<Range<u64>> for H64Range
+{
+    #[inline(always)]
+    fn from(other: Range<u64>) -> Self
+    {
+        Self(other)
+    }
+}
+
+/// Iterate over the [`H64`]s representing a range of [`u64`]s.
+///
+/// This is a shorthand for `range.map(H64::hash)`,
+/// which is also double-ended and, on 64-bit targets, exact-size.
+#[inline(always)]
+pub const fn h64_range(range: Range<u64>) -> H64Range
+{
+    H64Range(range)
+}
+
+/// Utility for generating [`H64`]s, recycling freed ones.
+///
+/// [`alloc`] reuses the most recently [`free`]d [`H64`] if there is one,
+/// and otherwise allocates a new one.
+/// In debug builds, freeing a [`H64`] that is already free panics.
+///
+/// [`alloc`]: `Self::alloc`
+/// [`free`]: `Self::free`
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+pub struct H64Pool
+{
+    allocator: H64Allocator,
+    free: Vec<H64>,
+    #[cfg(debug_assertions)]
+    is_free: H64HashSet<H64>,
+}
+
+#[cfg(feature = "alloc")]
+impl H64Pool
+{
+    /// Create a new pool.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self::with_allocator(H64Allocator::new())
+    }
+
+    /// Create a new pool that allocates new [`H64`]s from `allocator`.
+    #[inline]
+    pub fn with_allocator(allocator: H64Allocator) -> Self
+    {
+        Self {
+            allocator,
+            free: Vec::new(),
+            #[cfg(debug_assertions)]
+            is_free: H64HashSet::default(),
+        }
+    }
+
+    /// Create a pool that continues where another left off.
+    ///
+    /// `position` and `freed` must be the [`position`] and [`freed`] of the other pool.
+    /// The new pool then allocates exactly what the other would have.
+    ///
+    /// [`position`]: `Self::position`
+    /// [`freed`]: `Self::freed`
+    pub fn resume(position: u64, freed: Vec<H64>) -> Self
+    {
+        let mut this = Self::with_allocator(H64Allocator::resume(position));
+        for h64 in freed {
+            this.free(h64);
+        }
+        this
+    }
+
+    /// The [`position`] of the underlying allocator.
+    ///
+    /// [`position`]: `H64Allocator::position`
+    #[inline(always)]
+    pub fn position(&self) -> u64
+    {
+        self.allocator.position()
+    }
+
+    /// The free [`H64`]s, in the order they were freed.
+    #[inline(always)]
+    pub fn freed(&self) -> &[H64]
+    {
+        &self.free
+    }
+
+    /// Allocate a [`H64`], reusing a freed one if possible.
+    ///
+    /// Panics if there are no free [`H64`]s and the allocator is exhausted.
+    #[inline]
+    pub fn alloc(&mut self) -> H64
+    {
+        self.try_alloc().expect("H64Pool exhausted")
+    }
+
+    /// Allocate a [`H64`], reusing a freed one if possible,
+    /// unless there are no free [`H64`]s and the allocator is exhausted.
+    #[inline]
+    pub fn try_alloc(&mut self) -> Option<H64>
+    {
+        match self.free.pop() {
+            Some(h64) => {
+                #[cfg(debug_assertions)]
+                self.is_free.remove(&h64);
+                Some(h64)
+            },
+            None => self.allocator.try_alloc(),
+        }
+    }
+
+    /// Return a [`H64`] to the pool, so that it may be allocated again.
+    ///
+    /// In debug builds, panics if the [`H64`] is already free
+    /// or was obviously not allocated from this pool.
+    #[inline]
+    pub fn free(&mut self, h64: H64)
+    {
+        debug_assert!(
+            h64.unhash() < self.allocator.position(),
+            "H64 was not allocated from this pool",
+        );
+        #[cfg(debug_assertions)]
+        assert!(self.is_free.insert(h64), "H64 freed twice");
+        self.free.push(h64);
+    }
+}
+
+/// [`H64`] that is returned to its [`H64Pool`] when dropped.
+///
+/// This frees the [`H64`] even if the code using it returns early or panics.
+/// The pool must not be borrowed when the guard is dropped.
+#[cfg(feature = "alloc")]
+pub struct H64Guard<'a>
+{
+    pool: &'a RefCell<H64Pool>,
+    h64: H64,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> H64Guard<'a>
+{
+    /// Allocate a [`H64`] from a pool.
+    #[inline]
+    pub fn new(pool: &'a RefCell<H64Pool>) -> Self
+    {
+        let h64 = pool.borrow_mut().alloc();
+        Self { pool, h64 }
+    }
+
+    /// The guarded [`H64`].
+    #[inline(always)]
+    pub fn get(&self) -> H64
+    {
+        self.h64
+    }
+
+    /// Drop the guard without returning the [`H64`] to the pool.
+    #[inline]
+    pub fn leak(self) -> H64
+    {
+        let h64 = self.h64;
+        mem::forget(self);
+        h64
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for H64Guard<'_>
+{
+    fn drop(&mut self)
+    {
+        self.pool.borrow_mut().free(self.h64);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64Guard<'_>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_tuple("H64Guard").field(&self.h64).finish()
+    }
+}
+
+/// Utility for generating [`H64`]s from multiple threads.
+///
+/// Like [`H64Allocator`], but [`alloc`] takes a shared reference.
+/// The counter is an [`AtomicU64`], so allocation is lock-free.
+/// Like [`H64Allocator`], it is exhausted once it reaches [`u64::MAX`].
+///
+/// [`alloc`]: `Self::alloc`
+#[cfg(target_has_atomic = "64")]
+#[derive(Default)]
+pub struct AtomicH64Allocator(AtomicU64);
+
+#[cfg(target_has_atomic = "64")]
+impl AtomicH64Allocator
+{
+    /// Create a new allocator.
+    #[inline(always)]
+    pub const fn new() -> Self
+    {
+        Self(AtomicU64::new(0))
+    }
+
+    /// Create a new allocator whose first [`H64`] represents `offset`.
+    #[inline(always)]
+    pub const fn starting_at(offset: u64) -> Self
+    {
+        Self(AtomicU64::new(offset))
+    }
+
+    /// Create an allocator that continues where another left off.
+    ///
+    /// See [`H64Allocator::resume`].
+    #[inline(always)]
+    pub const fn resume(position: u64) -> Self
+    {
+        Self::starting_at(position)
+    }
+
+    /// Borrow the counter as a [`SharedH64Allocator`].
+    #[inline(always)]
+    pub const fn as_shared(&self) -> SharedH64Allocator<'_>
+    {
+        SharedH64Allocator::new(&self.0)
+    }
+
+    /// The original [`u64`] of the next [`H64`] to be allocated.
+    ///
+    /// This is [`u64::MAX`] if the allocator is exhausted.
+    /// Other threads may allocate in the meantime,
+    /// so the result may be outdated by the time it is returned.
+    #[inline]
+    pub fn position(&self) -> u64
+    {
+        self.as_shared().position()
+    }
+
+    /// Whether the allocator has run out of [`H64`]s.
+    #[inline]
+    pub fn is_exhausted(&self) -> bool
+    {
+        self.as_shared().is_exhausted()
+    }
+
+    /// Allocate the next [`H64`].
+    ///
+    /// Panics if the allocator is exhausted.
+    #[inline]
+    pub fn alloc(&self) -> H64
+    {
+        self.try_alloc().expect("AtomicH64Allocator exhausted")
+    }
+
+    /// Allocate the next [`H64`], unless the allocator is exhausted.
+    #[inline]
+    pub fn try_alloc(&self) -> Option<H64>
+    {
+        self.as_shared().try_alloc()
+    }
+
+    /// Allocate the next `n` [`H64`]s.
+    ///
+    /// See [`H64Allocator::alloc_range`].
+    /// Panics if fewer than `n` [`H64`]s are left.
+    #[inline]
+    pub fn alloc_range(&self, n: u64) -> H64Range
+    {
+        self.try_alloc_range(n).expect("AtomicH64Allocator exhausted")
+    }
+
+    /// Allocate the next `n` [`H64`]s, unless fewer are left.
+    #[inline]
+    pub fn try_alloc_range(&self, n: u64) -> Option<H64Range>
+    {
+        self.as_shared().try_alloc_range(n)
+    }
+}
+
+#[cfg(all(feature = "global", target_has_atomic = "64"))]
+static GLOBAL_ALLOCATOR: AtomicH64Allocator = AtomicH64Allocator::new();
+
+/// Allocate the next [`H64`] from the allocator shared by the whole program.
+///
+/// Every call returns a different [`H64`].
+/// Panics if the allocator is exhausted.
+#[cfg(all(feature = "global", target_has_atomic = "64"))]
+#[inline]
+pub fn alloc_global() -> H64
+{
+    GLOBAL_ALLOCATOR.alloc()
+}
+
+/// Utility for generating [`H64`]s from a borrowed counter.
+///
+/// Like [`AtomicH64Allocator`], but the counter lives elsewhere,
+/// for example in shared memory mapped into several processes.
+/// Every process that allocates from the counter then gets unique [`H64`]s,
+/// without coordinating through anything but the counter.
+#[cfg(target_has_atomic = "64")]
+#[derive(Clone, Copy)]
+pub struct SharedH64Allocator<'a>(&'a AtomicU64);
+
+#[cfg(target_has_atomic = "64")]
+impl<'a> SharedH64Allocator<'a>
+{
+    /// Allocate from a counter.
+    ///
+    /// The counter is the original [`u64`] of the next [`H64`] to be allocated,
+    /// so zero-initialized memory is a fresh allocator.
+    #[inline(always)]
+    pub const fn new(counter: &'a AtomicU64) -> Self
+    {
+        Self(counter)
+    }
+
+    /// Allocate from a counter behind a raw pointer,
+    /// such as one into a memory-mapped file.
+    ///
+    /// # Safety
+    ///
+    /// See [`AtomicU64::from_ptr`].
+    /// In particular, `counter` must be aligned to 8 bytes,
+    /// and for the duration of `'a`, no process may access it non-atomically.
+    #[inline(always)]
+    pub unsafe fn from_ptr(counter: *mut u64) -> Self
+    {
+        // SAFETY: Upheld by the caller.
+        Self(unsafe { AtomicU64::from_ptr(counter) })
+    }
+
+    /// The original [`u64`] of the next [`H64`] to be allocated.
+    ///
+    /// See [`AtomicH64Allocator::position`].
+    #[inline]
+    pub fn position(self) -> u64
+    {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Whether the allocator has run out of [`H64`]s.
+    #[inline]
+    pub fn is_exhausted(self) -> bool
+    {
+        self.position() == u64::MAX
+    }
+
+    /// Allocate the next [`H64`].
+    ///
+    /// Panics if the allocator is exhausted.
+    #[inline]
+    pub fn alloc(self) -> H64
+    {
+        self.try_alloc().expect("SharedH64Allocator exhausted")
+    }
+
+    /// Allocate the next [`H64`], unless the allocator is exhausted.
+    #[inline]
+    pub fn try_alloc(self) -> Option<H64>
+    {
+        // A plain fetch_add would wrap around and reissue H64s.
+        self.0.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |i| i.checked_add(1))
+            .ok()
+            .map(H64::hash)
+    }
+
+    /// Allocate the next `n` [`H64`]s.
+    ///
+    /// See [`H64Allocator::alloc_range`].
+    /// Panics if fewer than `n` [`H64`]s are left.
+    #[inline]
+    pub fn alloc_range(self, n: u64) -> H64Range
+    {
+        self.try_alloc_range(n).expect("SharedH64Allocator exhausted")
+    }
+
+    /// Allocate the next `n` [`H64`]s, unless fewer are left.
+    #[inline]
+    pub fn try_alloc_range(self, n: u64) -> Option<H64Range>
+    {
+        self.0.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |i| i.checked_add(n))
+            .ok()
+            .map(|start| H64Range(start .. start + n))
+    }
+
+    /// Allocate the next `n` [`H64`]s, or fewer if fewer are left.
+    fn lease(self, n: u64) -> H64Range
+    {
+        let lease = |i: u64| (i != u64::MAX).then(|| i.saturating_add(n));
+        match self.0.fetch_update(Ordering::Relaxed, Ordering::Relaxed, lease) {
+            Ok(start) => H64Range(start .. start.saturating_add(n)),
+            Err(_) => H64Range(u64::MAX .. u64::MAX),
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<'a> From<&'a AtomicH64Allocator> for SharedH64Allocator<'a>
+{
+    #[inline(always)]
+    fn from(other: &'a AtomicH64Allocator) -> Self
+    {
+        other.as_shared()
+    }
+}
+
+/// Utility for generating [`H64`]s on one thread,
+/// leasing blocks of them from an [`AtomicH64Allocator`] or [`SharedH64Allocator`].
+///
+/// Most allocations only touch the current block,
+/// so threads seldom contend on the shared counter.
+/// [`H64`]s left in the block when the allocator is dropped are never allocated.
+#[cfg(target_has_atomic = "64")]
+pub struct LocalH64Allocator<'a>
+{
+    shared: SharedH64Allocator<'a>,
+    block: H64Range,
+    block_size: u64,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<'a> LocalH64Allocator<'a>
+{
+    /// Create an allocator that leases `block_size` [`H64`]s at a time.
+    ///
+    /// No block is leased until the first allocation.
+    /// Panics if `block_size` is zero.
+    #[inline]
+    pub fn new(shared: impl Into<SharedH64Allocator<'a>>, block_size: u64) -> Self
+    {
+        assert!(block_size > 0, "block size must be nonzero");
+        Self { shared: shared.into(), block: H64Range(0 .. 0), block_size }
+    }
+
+    /// Allocate the next [`H64`].
+    ///
+    /// Panics if the block is used up and the shared allocator is exhausted.
+    #[inline]
+    pub fn alloc(&mut self) -> H64
+    {
+        self.try_alloc().expect("LocalH64Allocator exhausted")
+    }
+
+    /// Allocate the next [`H64`],
+    /// unless the block is used up and the shared allocator is exhausted.
+    #[inline]
+    pub fn try_alloc(&mut self) -> Option<H64>
+    {
+        self.block.next().or_else(|| {
+            self.block = self.shared.lease(self.block_size);
+            self.block.next()
+        })
+    }
+}
+
+/// [`H64`] made of an index and a generation.
+///
+/// Both are packed into the original [`u64`] before hashing:
+/// the generation in the high half and the index in the low half.
+/// See [`GenerationalAllocator`].
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct GenerationalH64(H64);
+
+impl GenerationalH64
+{
+    /// Create a [`GenerationalH64`] from an index and a generation.
+    #[inline]
+    pub const fn new(index: u32, generation: u32) -> Self
+    {
+        Self(H64::pack(generation, index))
+    }
+
+    /// Decode a [`H64`] into an index and a generation.
+    ///
+    /// Every [`H64`] decodes to some index and generation,
+    /// but only those produced by [`GenerationalH64::new`] are meaningful.
+    #[inline(always)]
+    pub const fn from_h64(h64: H64) -> Self
+    {
+        Self(h64)
+    }
+
+    /// Obtain the index.
+    #[inline]
+    pub const fn index(self) -> u32
+    {
+        self.0.lo()
+    }
+
+    /// Obtain the generation.
+    #[inline]
+    pub const fn generation(self) -> u32
+    {
+        self.0.hi()
+    }
+
+    /// Whether this ID reuses the index of another, but with a later generation.
+    ///
+    /// A stale ID can be detected by checking
+    /// whether the current ID for its index is newer than it.
+    #[inline]
+    pub const fn is_newer_than(self, other: Self) -> bool
+    {
+        self.index() == other.index() && self.generation() > other.generation()
+    }
+
+    /// Convert the [`GenerationalH64`] to a [`H64`].
+    #[inline(always)]
+    pub const fn get(self) -> H64
+    {
+        self.0
+    }
+}
+
+impl From<H64> for GenerationalH64
+{
+    #[inline(always)]
+    fn from(other: H64) -> Self
+    {
+        Self::from_h64(other)
+    }
+}
+
+impl From<GenerationalH64> for H64
+{
+    #[inline(always)]
+    fn from(other: GenerationalH64) -> Self
+    {
+        other.get()
+    }
+}
+
+impl fmt::Debug for GenerationalH64
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("GenerationalH64")
+            .field("index", &self.index())
+            .field("generation", &self.generation())
+            .finish()
+    }
+}
+
+impl fmt::Display for GenerationalH64
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+/// Entity ID for entity component systems.
+///
+/// This is a [`GenerationalH64`], with a 32-bit index and a 32-bit generation.
+/// Allocate them with an [`EntityAllocator`].
+pub type EntityId = GenerationalH64;
+
+/// Utility for generating [`GenerationalH64`]s.
+///
+/// Freed indices are reused with the next generation,
+/// so that stale IDs can be told apart from their replacements.
+/// An index whose generation would wrap around is never reused.
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+pub struct GenerationalAllocator
+{
+    /// Current generation of each index, and whether it is allocated.
+    slots: Vec<(u32, bool)>,
+    free: Vec<u32>,
+}
+
+#[cfg(feature = "alloc")]
+impl GenerationalAllocator
+{
+    /// Create a new allocator.
+    #[inline]
+    pub const fn new() -> Self
+    {
+        Self { slots: Vec::new(), free: Vec::new() }
+    }
+
+    /// Allocate a [`GenerationalH64`], reusing a freed index if possible.
+    ///
+    /// Panics if all indices are in use.
+    #[inline]
+    pub fn alloc(&mut self) -> GenerationalH64
+    {
+        self.try_alloc().expect("GenerationalAllocator exhausted")
+    }
+
+    /// Allocate a [`GenerationalH64`], reusing a freed index if possible,
+    /// unless all indices are in use.
+    pub fn try_alloc(&mut self) -> Option<GenerationalH64>
+    {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                let index = u32::try_from(self.slots.len()).ok()?;
+                self.slots.push((0, false));
+                index
+            },
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.1 = true;
+        Some(GenerationalH64::new(index, slot.0))
+    }
+
+    /// Free a [`GenerationalH64`], so that its index may be reused.
+    ///
+    /// Returns whether the ID was allocated;
+    /// freeing a stale ID does nothing.
+    pub fn free(&mut self, id: GenerationalH64) -> bool
+    {
+        if !self.is_allocated(id) {
+            return false;
+        }
+        let slot = &mut self.slots[id.index() as usize];
+        slot.1 = false;
+        if let Some(generation) = slot.0.checked_add(1) {
+            slot.0 = generation;
+            self.free.push(id.index());
+        }
+        true
+    }
+
+    /// Whether an ID is allocated, that is, not yet freed.
+    #[inline]
+    pub fn is_allocated(&self, id: GenerationalH64) -> bool
+    {
+        self.slots.get(id.index() as usize) == Some(&(id.generation(), true))
+    }
+}
+
+/// Utility for generating [`EntityId`]s.
+///
+/// This is a [`GenerationalAllocator`],
+/// which bumps the generation when reusing an index.
+#[cfg(feature = "alloc")]
+pub type EntityAllocator = GenerationalAllocator;
+
+/// [`H64`] whose original [`u64`] carries a tag in its top `BITS` bits.
+///
+/// The remaining bits hold the value.
+/// Tagging lets several kinds of ID share one [`u64`] field
+/// while remaining distinguishable after unhashing.
+/// `BITS` must be between 1 and 63.
+/// See [`TaggedH64Allocator`].
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct TaggedH64<const BITS: u32>(H64);
+
+impl<const BITS: u32> TaggedH64<BITS>
+{
+    const VALUE_BITS: u32 = {
+        assert!(0 < BITS && BITS < 64, "TaggedH64 must have 1 to 63 tag bits");
+        64 - BITS
+    };
+
+    /// The largest tag.
+    pub const TAG_MAX: u64 = u64::MAX >> Self::VALUE_BITS;
+
+    /// The largest value.
+    pub const VALUE_MAX: u64 = u64::MAX >> BITS;
+
+    /// Create a [`TaggedH64`] from a tag and a value.
+    ///
+    /// Panics if the tag exceeds [`TAG_MAX`]
+    /// or the value exceeds [`VALUE_MAX`].
+    ///
+    /// [`TAG_MAX`]: `Self::TAG_MAX`
+    /// [`VALUE_MAX`]: `Self::VALUE_MAX`
+    #[inline]
+    pub const fn new(tag: u64, value: u64) -> Self
+    {
+        match Self::try_new(tag, value) {
+            Some(tagged) => tagged,
+            None => panic!("TaggedH64 tag or value out of range"),
+        }
+    }
+
+    /// Create a [`TaggedH64`] from a tag and a value,
+    /// unless either is out of range.
+    #[inline]
+    pub const fn try_new(tag: u64, value: u64) -> Option<Self>
+    {
+        if tag > Self::TAG_MAX || value > Self::VALUE_MAX {
+            return None;
+        }
+        Some(Self(H64::hash(tag << Self::VALUE_BITS | value)))
+    }
+
+    /// Decode a [`H64`] into a tag and a value.
+    ///
+    /// Every [`H64`] decodes to some tag and value.
+    #[inline(always)]
+    pub const fn from_h64(h64: H64) -> Self
+    {
+        Self(h64)
+    }
+
+    /// Obtain the tag.
+    #[inline]
+    pub const fn tag(self) -> u64
+    {
+        self.0.unhash() >> Self::VALUE_BITS
+    }
+
+    /// Obtain the value.
+    #[inline]
+    pub const fn value(self) -> u64
+    {
+        self.0.unhash() & Self::VALUE_MAX
+    }
+
+    /// Convert the [`TaggedH64`] to a [`H64`].
+    #[inline(always)]
+    pub const fn get(self) -> H64
+    {
+        self.0
+    }
+}
+
+impl<const BITS: u32> From<H64> for TaggedH64<BITS>
+{
+    #[inline(always)]
+    fn from(other: H64) -> Self
+    {
+        Self::from_h64(other)
+    }
+}
+
+impl<const BITS: u32> From<TaggedH64<BITS>> for H64
+{
+    #[inline(always)]
+    fn from(other: TaggedH64<BITS>) -> Self
+    {
+        other.get()
+    }
+}
+
+impl<const BITS: u32> fmt::Debug for TaggedH64<BITS>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("TaggedH64")
+            .field("tag", &self.tag())
+            .field("value", &self.value())
+            .finish()
+    }
+}
+
+impl<const BITS: u32> fmt::Display for TaggedH64<BITS>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+/// Utility for generating [`TaggedH64`]s with a fixed tag.
+///
+/// The allocator hashes consecutive values under its tag.
+/// Allocators with different tags never produce the same [`TaggedH64`].
+#[derive(Clone, Debug)]
+pub struct TaggedH64Allocator<const BITS: u32>
+{
+    tag: u64,
+    next: u64,
+}
+
+impl<const BITS: u32> TaggedH64Allocator<BITS>
+{
+    /// Create a new allocator for a tag.
+    ///
+    /// Panics if the tag exceeds [`TaggedH64::TAG_MAX`].
+    #[inline]
+    pub const fn new(tag: u64) -> Self
+    {
+        assert!(tag <= TaggedH64::<BITS>::TAG_MAX, "TaggedH64 tag out of range");
+        Self { tag, next: 0 }
+    }
+
+    /// The tag of the allocated [`TaggedH64`]s.
+    #[inline(always)]
+    pub const fn tag(&self) -> u64
+    {
+        self.tag
+    }
+
+    /// The value of the next [`TaggedH64`] to be allocated.
+    #[inline(always)]
+    pub const fn position(&self) -> u64
+    {
+        self.next
+    }
+
+    /// Whether the allocator has run out of values.
+    #[inline(always)]
+    pub const fn is_exhausted(&self) -> bool
+    {
+        self.next > TaggedH64::<BITS>::VALUE_MAX
+    }
+
+    /// Allocate the next [`TaggedH64`].
+    ///
+    /// Panics if the allocator is exhausted.
+    #[inline]
+    pub const fn alloc(&mut self) -> TaggedH64<BITS>
+    {
+        self.try_alloc().expect("TaggedH64Allocator exhausted")
+    }
+
+    /// Allocate the next [`TaggedH64`], unless the allocator is exhausted.
+    #[inline]
+    pub const fn try_alloc(&mut self) -> Option<TaggedH64<BITS>>
+    {
+        let tagged = TaggedH64::try_new(self.tag, self.next);
+        if tagged.is_some() {
+            self.next += 1;
+        }
+        tagged
+    }
+}
+
+/// [`H64`] allocated from an [`H64Namespace`].
+///
+/// The top 8 bits of the original [`u64`] hold the namespace,
+/// and the remaining 56 bits hold the local value.
+pub type NamespacedH64 = TaggedH64<{ H64Namespace::BITS }>;
+
+impl NamespacedH64
+{
+    /// Obtain the namespace.
+    #[inline]
+    pub const fn namespace(self) -> u8
+    {
+        self.tag() as u8
+    }
+
+    /// Obtain the value local to the namespace.
+    #[inline]
+    pub const fn local(self) -> u64
+    {
+        self.value()
+    }
+}
+
+/// Utility for generating [`NamespacedH64`]s within one namespace.
+///
+/// Each namespace owns a disjoint part of the [`H64`]s,
+/// so subsystems with different namespaces can allocate
+/// independently, without coordination.
+#[derive(Clone, Debug)]
+pub struct H64Namespace(TaggedH64Allocator<{ H64Namespace::BITS }>);
+
+impl H64Namespace
+{
+    /// The number of bits taken up by the namespace.
+    pub const BITS: u32 = 8;
+
+    /// Create a new allocator for a namespace.
+    #[inline]
+    pub const fn new(namespace: u8) -> Self
+    {
+        Self(TaggedH64Allocator::new(namespace as u64))
+    }
+
+    /// Create an allocator for a namespace that continues where another left off.
+    ///
+    /// `position` must be the [`position`](Self::position) of the other allocator.
+    #[inline]
+    pub const fn resume(namespace: u8, position: u64) -> Self
+    {
+        Self(TaggedH64Allocator { tag: namespace as u64, next: position })
+    }
+
+    /// The namespace of the allocated [`NamespacedH64`]s.
+    #[inline(always)]
+    pub const fn namespace(&self) -> u8
+    {
+        self.0.tag() as u8
+    }
+
+    /// The local value of the next [`NamespacedH64`] to be allocated.
+    #[inline(always)]
+    pub const fn position(&self) -> u64
+    {
+        self.0.position()
+    }
+
+    /// Whether a [`H64`] belongs to this namespace.
+    #[inline]
+    pub const fn contains(&self, h64: H64) -> bool
+    {
+        NamespacedH64::from_h64(h64).namespace() == self.namespace()
+    }
+
+    /// Allocate the next [`NamespacedH64`].
+    ///
+    /// Panics if the namespace is exhausted.
+    #[inline]
+    pub const fn alloc(&mut self) -> NamespacedH64
+    {
+        self.try_alloc().expect("H64Namespace exhausted")
+    }
+
+    /// Allocate the next [`NamespacedH64`], unless the namespace is exhausted.
+    #[inline]
+    pub const fn try_alloc(&mut self) -> Option<NamespacedH64>
+    {
+        self.0.try_alloc()
+    }
+}
+
+/// Bit layout of the IDs generated by [`H64SnowflakeGenerator`].
+///
+/// From most to least significant bit, the original [`u64`] consists of
+/// a timestamp, a machine ID, and a sequence number.
+/// Bits above the timestamp are zero.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct H64SnowflakeLayout
+{
+    timestamp_bits: u32,
+    machine_bits: u32,
+    sequence_bits: u32,
+}
+
+impl H64SnowflakeLayout
+{
+    /// The layout popularized by Twitter:
+    /// 41 bits of milliseconds, 10 bits of machine ID, and 12 bits of sequence.
+    pub const TWITTER: Self = Self::new(41, 10, 12);
+
+    /// Create a layout with the given field widths.
+    ///
+    /// Panics if the widths add up to more than 64.
+    pub const fn new(timestamp_bits: u32, machine_bits: u32, sequence_bits: u32) -> Self
+    {
+        assert!(
+            timestamp_bits as u64 + machine_bits as u64 + sequence_bits as u64 <= 64,
+            "snowflake fields must fit in 64 bits",
+        );
+        Self { timestamp_bits, machine_bits, sequence_bits }
+    }
+
+    /// The largest value of a field of the given width.
+    const fn max(bits: u32) -> u64
+    {
+        if bits == 0 { 0 } else { u64::MAX >> (64 - bits) }
+    }
+
+    /// Shift left, shifting out all bits if the shift is 64.
+    #[cfg(feature = "std")]
+    const fn shl(i: u64, bits: u32) -> u64
+    {
+        match i.checked_shl(bits) { Some(i) => i, None => 0 }
+    }
+
+    /// Shift right, shifting out all bits if the shift is 64.
+    const fn shr(i: u64, bits: u32) -> u64
+    {
+        match i.checked_shr(bits) { Some(i) => i, None => 0 }
+    }
+
+    #[cfg(feature = "std")]
+    const fn pack(self, timestamp: u64, machine_id: u64, sequence: u64) -> u64
+    {
+        Self::shl(timestamp, self.machine_bits + self.sequence_bits)
+            | Self::shl(machine_id, self.sequence_bits)
+            | sequence
+    }
+
+    /// Decode the fields of an ID generated with this layout.
+    ///
+    /// Returns [`None`] if bits above the timestamp are set,
+    /// in which case the ID was not generated with this layout.
+    pub const fn decode(self, h64: H64) -> Option<H64Snowflake>
+    {
+        let i = h64.unhash();
+        let timestamp = Self::shr(i, self.machine_bits + self.sequence_bits);
+        if timestamp > Self::max(self.timestamp_bits) {
+            return None;
+        }
+        Some(H64Snowflake {
+            timestamp,
+            machine_id: Self::shr(i, self.sequence_bits) & Self::max(self.machine_bits),
+            sequence: i & Self::max(self.sequence_bits),
+        })
+    }
+}
+
+/// Fields of an ID generated by [`H64SnowflakeGenerator`].
+///
+/// See [`H64SnowflakeLayout::decode`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct H64Snowflake
+{
+    timestamp: u64,
+    machine_id: u64,
+    sequence: u64,
+}
+
+impl H64Snowflake
+{
+    /// Milliseconds between the epoch and the generation of the ID.
+    #[inline(always)]
+    pub const fn timestamp(self) -> u64
+    {
+        self.timestamp
+    }
+
+    /// Machine ID of the generator.
+    #[inline(always)]
+    pub const fn machine_id(self) -> u64
+    {
+        self.machine_id
+    }
+
+    /// Sequence number of the ID within its millisecond.
+    #[inline(always)]
+    pub const fn sequence(self) -> u64
+    {
+        self.sequence
+    }
+
+    /// Time at which the ID was generated.
+    ///
+    /// `epoch` must be the epoch of the generator.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn system_time(self, epoch: SystemTime) -> SystemTime
+    {
+        epoch + Duration::from_millis(self.timestamp)
+    }
+}
+
+impl Default for H64SnowflakeLayout
+{
+    fn default() -> Self
+    {
+        Self::TWITTER
+    }
+}
+
+/// Generator of roughly time-ordered [`H64`]s.
+///
+/// Each ID packs the number of milliseconds since an epoch,
+/// a machine ID, and a sequence number into the original [`u64`]
+/// as described by a [`H64SnowflakeLayout`].
+/// Ordered by original [`u64`], IDs from one generator are ordered by time,
+/// and IDs from many generators roughly so.
+#[cfg(feature = "std")]
+pub struct H64SnowflakeGenerator
+{
+    layout: H64SnowflakeLayout,
+    machine_id: u64,
+    epoch: SystemTime,
+    last_timestamp: u64,
+    sequence: u64,
+}
+
+#[cfg(feature = "std")]
+impl H64SnowflakeGenerator
+{
+    /// Create a generator.
+    ///
+    /// Timestamps count milliseconds since `epoch`.
+    /// Panics if `machine_id` does not fit in the layout.
+    pub fn new(layout: H64SnowflakeLayout, machine_id: u64, epoch: SystemTime) -> Self
+    {
+        assert!(
+            machine_id <= H64SnowflakeLayout::max(layout.machine_bits),
+            "machine ID must fit in the layout",
+        );
+        Self { layout, machine_id, epoch, last_timestamp: 0, sequence: 0 }
+    }
+
+    /// The layout of the generated IDs.
+    #[inline(always)]
+    pub fn layout(&self) -> H64SnowflakeLayout
+    {
+        self.layout
+    }
+
+    /// The epoch of the timestamps of the generated IDs.
+    #[inline(always)]
+    pub fn epoch(&self) -> SystemTime
+    {
+        self.epoch
+    }
+
+    /// Generate an ID for the current time.
+    ///
+    /// If all sequence numbers for the current millisecond are used up,
+    /// this waits for the next millisecond.
+    /// Panics if the current time is before the epoch
+    /// or too far after it to fit in the layout.
+    pub fn generate(&mut self) -> H64
+    {
+        loop {
+            let elapsed = SystemTime::now().duration_since(self.epoch)
+                .expect("clock is before the snowflake epoch");
+            let timestamp = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+            assert!(
+                timestamp <= H64SnowflakeLayout::max(self.layout.timestamp_bits),
+                "snowflake timestamp overflow",
+            );
+            if let Some(h64) = self.generate_at(timestamp) {
+                return h64;
+            }
+            thread::yield_now();
+        }
+    }
+
+    /// Generate an ID for the given timestamp.
+    ///
+    /// A timestamp earlier than the previous one is treated as the previous one,
+    /// so that IDs remain ordered if the clock goes backwards.
+    /// Returns [`None`] if the timestamp does not fit in the layout
+    /// or all sequence numbers for it are used up.
+    pub fn generate_at(&mut self, timestamp: u64) -> Option<H64>
+    {
+        let layout = self.layout;
+        let timestamp = timestamp.max(self.last_timestamp);
+        if timestamp > H64SnowflakeLayout::max(layout.timestamp_bits) {
+            return None;
+        }
+        if timestamp > self.last_timestamp {
+            self.last_timestamp = timestamp;
+            self.sequence = 0;
+        }
+        if self.sequence > H64SnowflakeLayout::max(layout.sequence_bits) {
+            return None;
+        }
+        let i = layout.pack(timestamp, self.machine_id, self.sequence);
+        self.sequence += 1;
+        Some(H64::hash(i))
+    }
+}
+
+/// Utility for generating [`H64Id`]s.
+pub struct H64IdAllocator<T: ?Sized>(H64Allocator, PhantomData<fn() -> T>);
+
+impl<T: ?Sized> H64IdAllocator<T>
+{
+    /// Create a new allocator.
+    #[inline(always)]
+    pub const fn new() -> Self
+    {
+        Self(H64Allocator::new(), PhantomData)
+    }
+
+    /// Allocate the next [`H64Id`].
+    ///
+    /// Panics if the allocator is exhausted.
+    #[inline]
+    pub const fn alloc(&mut self) -> H64Id<T>
+    {
+        H64Id::new(self.0.alloc())
+    }
+
+    /// Allocate the next [`H64Id`], unless the allocator is exhausted.
+    #[inline]
+    pub const fn try_alloc(&mut self) -> Option<H64Id<T>>
+    {
+        match self.0.try_alloc() {
+            Some(h64) => Some(H64Id::new(h64)),
+            None => None,
+        }
+    }
+}
+
+impl<T: ?Sized> Default for H64IdAllocator<T>
+{
+    #[inline(always)]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+/// Create a [`H64`] from a [`u64`] at compile time.
+///
+/// The argument must be a constant expression.
+/// The result can initialize constants,
+/// which in turn can be used as patterns.
+///
+/// ```
+/// use h64::{H64, h64};
+///
+/// const ROOT: H64 = h64!(0);
+///
+/// assert!(matches!(H64::hash(0), ROOT));
+/// ```
+#[macro_export]
+macro_rules! h64
+{
+    ($i:expr $(,)?) => {
+        const { $crate::H64::hash($i) }
+    };
+}
+
+/// Define a newtype over [`H64`].
+///
+/// The newtype is transparent, hashes like [`H64`] (so use it with [`H64Hasher`]),
+/// converts to and from [`H64`], and has `hash`, `unhash`, and `alloc` functions.
+/// Formatting reveals the original [`u64`], preceded by an optional prefix.
+/// Parsing with [`FromStr`](core::str::FromStr) requires the same prefix
+/// (see [`H64::parse_prefixed`]).
+///
+/// ```
+/// h64::define_h64_id! {
+///     /// Identifies a user.
+///     pub UserId, prefix = "usr_"
+/// }
+///
+/// assert_eq!(UserId::hash(42).to_string(), "usr_42");
+/// assert_eq!("usr_42".parse(), Ok(UserId::hash(42)));
+/// assert!("ord_42".parse::<UserId>().is_err());
+/// ```
+#[macro_export]
+macro_rules! define_h64_id
+{
+    ($(#[$attr:meta])* $vis:vis $name:ident $(,)?) => {
+        $crate::define_h64_id!($(#[$attr])* $vis $name, prefix = "");
+    };
+
+    ($(#[$attr:meta])* $vis:vis $name:ident, prefix = $prefix:expr $(,)?) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Eq, Hash, PartialEq)]
+        #[repr(transparent)]
+        $vis struct $name($crate::H64);
+
+        #[allow(dead_code)]
+        impl $name
+        {
+            /// Prefix written before the original [`u64`] when formatting.
+            pub const PREFIX: &'static str = $prefix;
+
+            /// Create an ID from a [`u64`].
+            #[inline]
+            pub const fn hash(i: u64) -> Self
+            {
+                Self($crate::H64::hash(i))
+            }
+
+            /// Obtain the original [`u64`].
+            #[inline]
+            pub const fn unhash(self) -> u64
+            {
+                self.0.unhash()
+            }
+
+            /// Allocate the next ID.
+            #[inline]
+            pub const fn alloc(allocator: &mut $crate::H64Allocator) -> Self
+            {
+                Self(allocator.alloc())
+            }
+        }
+
+        impl ::core::convert::From<$crate::H64> for $name
+        {
+            #[inline(always)]
+            fn from(other: $crate::H64) -> Self
+            {
+                Self(other)
+            }
+        }
+
+        impl ::core::convert::From<$name> for $crate::H64
+        {
+            #[inline(always)]
+            fn from(other: $name) -> Self
+            {
+                other.0
+            }
+        }
+
+        impl ::core::fmt::Debug for $name
+        {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result
+            {
+                f.debug_tuple(::core::stringify!($name)).field(&self.0).finish()
+            }
+        }
+
+        impl ::core::fmt::Display for $name
+        {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result
+            {
+                f.write_str(Self::PREFIX)?;
+                ::core::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl ::core::str::FromStr for $name
+        {
+            type Err = $crate::H64ParseError;
+
+            #[inline]
+            fn from_str(s: &str) -> ::core::result::Result<Self, $crate::H64ParseError>
+            {
+                $crate::H64::parse_prefixed(s, Self::PREFIX).map(Self)
+            }
+        }
+    };
+}
+
+/// Build a [`H64StaticMap`] at compile time.
+///
+/// Keys are [`u64`]s, which are hashed with [`H64::hash`].
+/// Keys and values must be constant expressions.
+/// The perfect hash function is computed during constant evaluation,
+/// so a large map makes compilation slow.
+/// Duplicate keys are a compile-time error.
+///
+/// ```
+/// use h64::{H64, H64StaticMap, h64_static_map};
+///
+/// static KEYWORDS: H64StaticMap<&str> = h64_static_map! {
+///     1 => "if",
+///     2 => "else",
+///     3 => "while",
+/// };
+///
+/// assert_eq!(KEYWORDS.get(H64::hash(2)), Some(&"else"));
+/// assert_eq!(KEYWORDS.get(H64::hash(4)), None);
+/// ```
+#[macro_export]
+macro_rules! h64_static_map
+{
+    ($($key:expr => $value:expr),* $(,)?) => {{
+        const KEYS: &[u64] = &[$($key),*];
+        const N: usize = KEYS.len();
+        const B: usize = $crate::H64StaticTable::<0, 0>::buckets_for(N);
+        const TABLE: $crate::H64StaticTable<N, B> = $crate::H64StaticTable::new(KEYS);
+        $crate::H64StaticMap::from_table(&TABLE, &const { [$($value),*] })
+    }};
+}
+
+/// Identity hasher for use with [`H64`].
+///
+/// This hasher only implements [`write_u64`].
+/// The implementation is the identity function.
+/// Its use improves hashing performance by ∞%.
+///
+/// [`write_u64`]: `Self::write_u64`
+#[derive(Default)]
+pub struct H64Hasher(u64);
+
+impl Hasher for H64Hasher
+{
+    #[inline(always)]
+    fn finish(&self) -> u64
+    {
+        self.0
+    }
+
+    fn write(&mut self, _bytes: &[u8])
+    {
+        unimplemented!("only use with H64")
+    }
+
+    #[inline(always)]
+    fn write_u64(&mut self, i: u64)
+    {
+        self.0 = i;
+    }
+}
+
+/// Identity hasher for use with [`H32`].
+///
+/// This hasher only implements [`write_u32`].
+/// The [`u32`] is repeated in both halves of the [`u64`] hash,
+/// so that hash tables which look at the high bits
+/// (such as the one underlying [`HashMap`]) see mixed bits there too.
+///
+/// [`write_u32`]: `Self::write_u32`
+#[derive(Default)]
+pub struct H32Hasher(u64);
+
+impl Hasher for H32Hasher
+{
+    #[inline(always)]
+    fn finish(&self) -> u64
+    {
+        self.0
+    }
+
+    fn write(&mut self, _bytes: &[u8])
+    {
+        unimplemented!("only use with H32")
+    }
+
+    #[inline(always)]
+    fn write_u32(&mut self, i: u32)
+    {
+        self.0 = u64::from(i) * 0x0000_0001_0000_0001;
+    }
+}
+
+/// Identity hasher for use with [`H128`].
+///
+/// This hasher only implements [`write_u128`].
+/// The hash is the low half of the [`u128`],
+/// which is as well mixed as the whole.
+///
+/// [`write_u128`]: `Self::write_u128`
+#[derive(Default)]
+pub struct H128Hasher(u64);
+
+impl Hasher for H128Hasher
+{
+    #[inline(always)]
+    fn finish(&self) -> u64
+    {
+        self.0
+    }
+
+    fn write(&mut self, _bytes: &[u8])
+    {
+        unimplemented!("only use with H128")
+    }
+
+    #[inline(always)]
+    fn write_u128(&mut self, i: u128)
+    {
+        self.0 = i as u64;
+    }
+}
+
+/// Specialization of [`HashMap`] that uses [`H64Hasher`].
+#[cfg(feature = "alloc")]
+pub type H64HashMap<K, V> = HashMap<K, V, BuildHasherDefault<H64Hasher>>;
+
+/// Specialization of [`HashSet`] that uses [`H64Hasher`].
+#[cfg(feature = "alloc")]
+pub type H64HashSet<T> = HashSet<T, BuildHasherDefault<H64Hasher>>;
+
+/// Specialization of [`HashMap`] that uses [`H32Hasher`].
+#[cfg(feature = "alloc")]
+pub type H32HashMap<K, V> = HashMap<K, V, BuildHasherDefault<H32Hasher>>;
+
+/// Specialization of [`HashSet`] that uses [`H32Hasher`].
+#[cfg(feature = "alloc")]
+pub type H32HashSet<T> = HashSet<T, BuildHasherDefault<H32Hasher>>;
+
+/// Specialization of [`HashMap`] that uses [`H128Hasher`].
+#[cfg(feature = "alloc")]
+pub type H128HashMap<K, V> = HashMap<K, V, BuildHasherDefault<H128Hasher>>;
+
+/// Specialization of [`HashSet`] that uses [`H128Hasher`].
+#[cfg(feature = "alloc")]
+pub type H128HashSet<T> = HashSet<T, BuildHasherDefault<H128Hasher>>;
+
+/// [`BTreeMap`] with [`H64`] keys, ordered by original [`u64`].
+#[cfg(feature = "alloc")]
+pub type H64BTreeMap<V> = BTreeMap<OrdByValue, V>;
+
+/// [`BTreeSet`] of [`H64`]s, ordered by original [`u64`].
+#[cfg(feature = "alloc")]
+pub type H64BTreeSet = BTreeSet<OrdByValue>;
+
+/// Iterate over a map with [`H64`] keys in order of original [`u64`].
+///
+/// Hash maps iterate in an order scrambled by the hash.
+/// This collects and sorts the entries, for deterministic output.
+#[cfg(feature = "alloc")]
+pub fn sorted_iter<'a, M, V>(map: &'a M) -> vec::IntoIter<(&'a H64, &'a V)>
+where
+    &'a M: IntoIterator<Item = (&'a H64, &'a V)>,
+    V: 'a,
+{
+    let mut entries: Vec<_> = map.into_iter().collect();
+    entries.sort_unstable_by_key(|(k, _)| k.unhash());
+    entries.into_iter()
+}
+
+/// Iterate over a set of [`H64`]s in order of original [`u64`].
+///
+/// Like [`sorted_iter`], but for sets.
+#[cfg(feature = "alloc")]
+pub fn sorted_keys<'a, S>(set: &'a S) -> vec::IntoIter<H64>
+where
+    &'a S: IntoIterator<Item = &'a H64>,
+{
+    let mut keys: Vec<_> = set.into_iter().copied().collect();
+    keys.sort_unstable_by_key(|k| k.unhash());
+    keys.into_iter()
+}
+
+/// Specialization of [`IndexMap`](::indexmap::IndexMap) that uses [`H64Hasher`].
+///
+/// Unlike [`H64HashMap`], this iterates in insertion order.
+#[cfg(feature = "indexmap")]
+pub type H64IndexMap<K, V> = ::indexmap::IndexMap<K, V, BuildHasherDefault<H64Hasher>>;
+
+/// Specialization of [`IndexSet`](::indexmap::IndexSet) that uses [`H64Hasher`].
+///
+/// Unlike [`H64HashSet`], this iterates in insertion order.
+#[cfg(feature = "indexmap")]
+pub type H64IndexSet<T> = ::indexmap::IndexSet<T, BuildHasherDefault<H64Hasher>>;
+
+/// Fixed-capacity map with [`H64`] keys that does not allocate.
+///
+/// This is a specialization of [`IndexMap`](::heapless::IndexMap) that uses [`H64Hasher`].
+/// `N` must be a power of two.
+/// Inserting into a full map returns the entry as an error.
+#[cfg(feature = "heapless")]
+pub type H64HeaplessMap<V, const N: usize> =
+    ::heapless::IndexMap<H64, V, core::hash::BuildHasherDefault<H64Hasher>, N>;
+
+/// Fixed-capacity set of [`H64`]s that does not allocate.
+///
+/// This is a specialization of [`IndexSet`](::heapless::IndexSet) that uses [`H64Hasher`].
+/// `N` must be a power of two.
+/// Inserting into a full set returns the element as an error.
+#[cfg(feature = "heapless")]
+pub type H64HeaplessSet<const N: usize> =
+    ::heapless::IndexSet<H64, core::hash::BuildHasherDefault<H64Hasher>, N>;
+
+/// Hash map with [`u64`] keys.
+///
+/// Keys are hashed with [`H64::hash`] on the way in
+/// and unhashed on the way out,
+/// so callers never deal with [`H64`]s directly.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct H64Map<V>(H64HashMap<H64, V>);
+
+#[cfg(feature = "alloc")]
+impl<V> H64Map<V>
+{
+    /// Create an empty map.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self(H64HashMap::default())
+    }
+
+    /// Create an empty map with room for at least `capacity` entries.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self
+    {
+        Self(H64HashMap::with_capacity_and_hasher(capacity, Default::default()))
+    }
+
+    /// The number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.0.len()
+    }
+
+    /// Whether the map has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.0.is_empty()
+    }
+
+    /// Insert an entry, returning the value previously stored under the key.
+    #[inline]
+    pub fn insert(&mut self, key: u64, value: V) -> Option<V>
+    {
+        self.0.insert(H64::hash(key), value)
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub fn get(&self, key: u64) -> Option<&V>
+    {
+        self.0.get(&H64::hash(key))
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub fn get_mut(&mut self, key: u64) -> Option<&mut V>
+    {
+        self.0.get_mut(&H64::hash(key))
+    }
+
+    /// Whether a value is stored under a key.
+    #[inline]
+    pub fn contains_key(&self, key: u64) -> bool
+    {
+        self.0.contains_key(&H64::hash(key))
+    }
+
+    /// Remove an entry, returning its value.
+    #[inline]
+    pub fn remove(&mut self, key: u64) -> Option<V>
+    {
+        self.0.remove(&H64::hash(key))
+    }
+
+    /// Remove all entries.
+    #[inline]
+    pub fn clear(&mut self)
+    {
+        self.0.clear();
+    }
+
+    /// Iterate over the entries in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &V)>
+    {
+        self.0.iter().map(|(k, v)| (k.unhash(), v))
+    }
+
+    /// Iterate over the entries in arbitrary order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u64, &mut V)>
+    {
+        self.0.iter_mut().map(|(k, v)| (k.unhash(), v))
+    }
+
+    /// Iterate over the keys in arbitrary order.
+    pub fn keys(&self) -> impl Iterator<Item = u64> + '_
+    {
+        self.0.keys().map(|k| k.unhash())
+    }
+
+    /// Iterate over the values in arbitrary order.
+    pub fn values(&self) -> impl Iterator<Item = &V>
+    {
+        self.0.values()
+    }
+
+    /// The underlying map, keyed by [`H64`].
+    #[inline(always)]
+    pub fn as_inner(&self) -> &H64HashMap<H64, V>
+    {
+        &self.0
+    }
+
+    /// The underlying map, keyed by [`H64`].
+    #[inline(always)]
+    pub fn into_inner(self) -> H64HashMap<H64, V>
+    {
+        self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> Default for H64Map<V>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> From<H64HashMap<H64, V>> for H64Map<V>
+{
+    #[inline(always)]
+    fn from(other: H64HashMap<H64, V>) -> Self
+    {
+        Self(other)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> Extend<(u64, V)> for H64Map<V>
+{
+    fn extend<I: IntoIterator<Item = (u64, V)>>(&mut self, iter: I)
+    {
+        self.0.extend(iter.into_iter().map(|(k, v)| (H64::hash(k), v)));
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> FromIterator<(u64, V)> for H64Map<V>
+{
+    fn from_iter<I: IntoIterator<Item = (u64, V)>>(iter: I) -> Self
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: PartialEq> PartialEq for H64Map<V>
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool
+    {
+        self.0 == other.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: Eq> Eq for H64Map<V>
+{
+}
+
+#[cfg(feature = "alloc")]
+impl<V: fmt::Debug> fmt::Debug for H64Map<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Hash set of [`u64`]s.
+///
+/// Elements are hashed with [`H64::hash`] on the way in
+/// and unhashed on the way out,
+/// so callers never deal with [`H64`]s directly.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct H64Set(H64HashSet<H64>);
+
+#[cfg(feature = "alloc")]
+impl H64Set
+{
+    /// Create an empty set.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self(H64HashSet::default())
+    }
+
+    /// Create an empty set with room for at least `capacity` elements.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self
+    {
+        Self(H64HashSet::with_capacity_and_hasher(capacity, Default::default()))
+    }
+
+    /// The number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.0.len()
+    }
+
+    /// Whether the set has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.0.is_empty()
+    }
+
+    /// Insert an element, returning whether it was not yet in the set.
+    #[inline]
+    pub fn insert(&mut self, i: u64) -> bool
+    {
+        self.0.insert(H64::hash(i))
+    }
+
+    /// Whether an element is in the set.
+    #[inline]
+    pub fn contains(&self, i: u64) -> bool
+    {
+        self.0.contains(&H64::hash(i))
+    }
+
+    /// Remove an element, returning whether it was in the set.
+    #[inline]
+    pub fn remove(&mut self, i: u64) -> bool
+    {
+        self.0.remove(&H64::hash(i))
+    }
+
+    /// Remove all elements.
+    #[inline]
+    pub fn clear(&mut self)
+    {
+        self.0.clear();
+    }
+
+    /// Iterate over the elements in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_
+    {
+        self.0.iter().map(|h64| h64.unhash())
+    }
+
+    /// The underlying set of [`H64`]s.
+    #[inline(always)]
+    pub fn as_inner(&self) -> &H64HashSet<H64>
+    {
+        &self.0
+    }
+
+    /// The underlying set of [`H64`]s.
+    #[inline(always)]
+    pub fn into_inner(self) -> H64HashSet<H64>
+    {
+        self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<H64HashSet<H64>> for H64Set
+{
+    #[inline(always)]
+    fn from(other: H64HashSet<H64>) -> Self
+    {
+        Self(other)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Extend<u64> for H64Set
+{
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I)
+    {
+        self.0.extend(iter.into_iter().map(H64::hash));
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<u64> for H64Set
+{
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> Self
+    {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64Set
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Lookups in [`H64HashMap`] by original [`u64`].
+///
+/// These hash the [`u64`] with [`H64::hash`],
+/// mirroring the API of [`H64Map`].
+#[cfg(feature = "alloc")]
+pub trait H64HashMapExt<V>
+{
+    /// Obtain the value stored under the hash of a [`u64`].
+    fn get_value(&self, key: u64) -> Option<&V>;
+
+    /// Obtain the value stored under the hash of a [`u64`].
+    fn get_value_mut(&mut self, key: u64) -> Option<&mut V>;
+
+    /// Whether a value is stored under the hash of a [`u64`].
+    fn contains_value(&self, key: u64) -> bool;
+
+    /// Insert a value under the hash of a [`u64`],
+    /// returning the value previously stored there.
+    fn insert_value(&mut self, key: u64, value: V) -> Option<V>;
+
+    /// Remove the value stored under the hash of a [`u64`].
+    fn remove_value(&mut self, key: u64) -> Option<V>;
+}
+
+#[cfg(feature = "alloc")]
+impl<V> H64HashMapExt<V> for H64HashMap<H64, V>
+{
+    #[inline]
+    fn get_value(&self, key: u64) -> Option<&V>
+    {
+        self.get(&H64::hash(key))
+    }
+
+    #[inline]
+    fn get_value_mut(&mut self, key: u64) -> Option<&mut V>
+    {
+        self.get_mut(&H64::hash(key))
+    }
+
+    #[inline]
+    fn contains_value(&self, key: u64) -> bool
+    {
+        self.contains_key(&H64::hash(key))
+    }
+
+    #[inline]
+    fn insert_value(&mut self, key: u64, value: V) -> Option<V>
+    {
+        self.insert(H64::hash(key), value)
+    }
+
+    #[inline]
+    fn remove_value(&mut self, key: u64) -> Option<V>
+    {
+        self.remove(&H64::hash(key))
+    }
+}
+
+/// Lookups in [`H64HashSet`] by original [`u64`].
+///
+/// These hash the [`u64`] with [`H64::hash`],
+/// mirroring the API of [`H64Set`].
+#[cfg(feature = "alloc")]
+pub trait H64HashSetExt
+{
+    /// Whether the hash of a [`u64`] is in the set.
+    fn contains_value(&self, i: u64) -> bool;
+
+    /// Insert the hash of a [`u64`], returning whether it was not yet in the set.
+    fn insert_value(&mut self, i: u64) -> bool;
+
+    /// Remove the hash of a [`u64`], returning whether it was in the set.
+    fn remove_value(&mut self, i: u64) -> bool;
+}
+
+#[cfg(feature = "alloc")]
+impl H64HashSetExt for H64HashSet<H64>
+{
+    #[inline]
+    fn contains_value(&self, i: u64) -> bool
+    {
+        self.contains(&H64::hash(i))
+    }
+
+    #[inline]
+    fn insert_value(&mut self, i: u64) -> bool
+    {
+        self.insert(H64::hash(i))
+    }
+
+    #[inline]
+    fn remove_value(&mut self, i: u64) -> bool
+    {
+        self.remove(&H64::hash(i))
+    }
+}
+
+/// Extension methods for iterators working with [`H64`]s.
+#[cfg(feature = "alloc")]
+pub trait H64IteratorExt: Iterator + Sized
+{
+    /// Collect key-value pairs into an [`H64HashMap`].
+    ///
+    /// Later values replace earlier values with the same key.
+    #[inline]
+    fn collect_h64_map<V>(self) -> H64HashMap<H64, V>
+        where Self: Iterator<Item = (H64, V)>
+    {
+        self.collect()
+    }
+
+    /// Collect [`H64`]s into an [`H64HashSet`].
+    #[inline]
+    fn collect_h64_set(self) -> H64HashSet<H64>
+        where Self: Iterator<Item = H64>
+    {
+        self.collect()
+    }
+
+    /// Group items by the [`H64`] computed from each.
+    ///
+    /// Within each group, items keep the order in which they were yielded.
+    fn group_by_h64<F>(self, mut key_fn: F) -> H64HashMap<H64, Vec<Self::Item>>
+        where F: FnMut(&Self::Item) -> H64
+    {
+        let mut groups = H64HashMap::<H64, Vec<Self::Item>>::default();
+        for item in self {
+            groups.entry(key_fn(&item)).or_default().push(item);
+        }
+        groups
+    }
+
+    /// Skip [`H64`]s that were already yielded.
+    ///
+    /// The seen [`H64`]s are kept in an [`H64HashSet`].
+    #[inline]
+    fn unique_h64(self) -> UniqueH64<Self>
+        where Self: Iterator<Item = H64>
+    {
+        self.unique_h64_with_capacity(0)
+    }
+
+    /// Like [`unique_h64`](Self::unique_h64),
+    /// but with room for `capacity` seen [`H64`]s up front.
+    #[inline]
+    fn unique_h64_with_capacity(self, capacity: usize) -> UniqueH64<Self>
+        where Self: Iterator<Item = H64>
+    {
+        let seen = H64HashSet::with_capacity_and_hasher(capacity, Default::default());
+        UniqueH64 { iter: self, seen }
+    }
+
+    /// Skip [`H64`]s that were probably already yielded.
+    ///
+    /// The seen [`H64`]s are kept in a [`H64BloomFilter`],
+    /// so memory use is bounded, but false positives cause
+    /// some [`H64`]s to be skipped even though they were not yet yielded.
+    /// Duplicates are always skipped.
+    #[inline]
+    fn unique_h64_approx(self, filter: H64BloomFilter) -> UniqueH64Approx<Self>
+        where Self: Iterator<Item = H64>
+    {
+        UniqueH64Approx { iter: self, filter }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I: Iterator> H64IteratorExt for I
+{
+}
+
+/// Iterator returned by [`H64IteratorExt::unique_h64`]
+/// and [`H64IteratorExt::unique_h64_with_capacity`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct UniqueH64<I>
+{
+    iter: I,
+    seen: H64HashSet<H64>,
+}
+
+#[cfg(feature = "alloc")]
+impl<I> UniqueH64<I>
+{
+    /// The [`H64`]s yielded so far.
+    #[inline(always)]
+    pub fn seen(&self) -> &H64HashSet<H64>
+    {
+        &self.seen
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I: Iterator<Item = H64>> Iterator for UniqueH64<I>
+{
+    type Item = H64;
+
+    #[inline]
+    fn next(&mut self) -> Option<H64>
+    {
+        let seen = &mut self.seen;
+        self.iter.find(|&h64| seen.insert(h64))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>)
+    {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I: FusedIterator<Item = H64>> FusedIterator for UniqueH64<I>
+{
+}
+
+/// Iterator returned by [`H64IteratorExt::unique_h64_approx`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct UniqueH64Approx<I>
+{
+    iter: I,
+    filter: H64BloomFilter,
+}
+
+#[cfg(feature = "alloc")]
+impl<I> UniqueH64Approx<I>
+{
+    /// The filter of [`H64`]s yielded so far.
+    #[inline(always)]
+    pub fn filter(&self) -> &H64BloomFilter
+    {
+        &self.filter
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I: Iterator<Item = H64>> Iterator for UniqueH64Approx<I>
+{
+    type Item = H64;
+
+    #[inline]
+    fn next(&mut self) -> Option<H64>
+    {
+        let filter = &mut self.filter;
+        self.iter.find(|&h64| filter.insert(h64))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>)
+    {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I: FusedIterator<Item = H64>> FusedIterator for UniqueH64Approx<I>
+{
+}
+
+/// Fixed-capacity map with [`H64`] keys stored inline.
+///
+/// Lookups scan the entries linearly, which beats hashing for small `N`.
+/// The map does not allocate, so it suits short-lived scratch tables.
+/// Inserting into a full map returns the entry as an error.
+#[derive(Clone)]
+pub struct H64ArrayMap<V, const N: usize>
+{
+    /// The first `len` entries are occupied, the others are not.
+    entries: [Option<(H64, V)>; N],
+    len: usize,
+}
+
+impl<V, const N: usize> H64ArrayMap<V, N>
+{
+    /// Create an empty map.
+    #[inline]
+    pub const fn new() -> Self
+    {
+        Self { entries: [const { None }; N], len: 0 }
+    }
+
+    /// The number of entries in the map.
+    #[inline(always)]
+    pub const fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    /// Whether the map has no entries.
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    /// The number of entries the map has room for.
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize
+    {
+        N
+    }
+
+    fn position(&self, key: H64) -> Option<usize>
+    {
+        self.entries[.. self.len].iter()
+            .position(|entry| matches!(entry, Some((k, _)) if *k == key))
+    }
+
+    /// Obtain the value stored under a key.
+    pub fn get(&self, key: H64) -> Option<&V>
+    {
+        self.iter().find(|&(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Obtain the value stored under a key.
+    pub fn get_mut(&mut self, key: H64) -> Option<&mut V>
+    {
+        self.iter_mut().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    /// Whether a value is stored under a key.
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.position(key).is_some()
+    }
+
+    /// Insert an entry, returning the value previously stored under the key.
+    ///
+    /// Returns an error if the key is new and the map is full.
+    pub fn insert(&mut self, key: H64, value: V) -> Result<Option<V>, CapacityError<(H64, V)>>
+    {
+        if let Some(old) = self.get_mut(key) {
+            return Ok(Some(mem::replace(old, value)));
+        }
+        if self.len == N {
+            return Err(CapacityError::new((key, value)));
+        }
+        self.entries[self.len] = Some((key, value));
+        self.len += 1;
+        Ok(None)
+    }
+
+    /// Remove an entry, returning its value.
+    ///
+    /// This moves the last entry into the place of the removed one.
+    pub fn remove(&mut self, key: H64) -> Option<V>
+    {
+        let index = self.position(key)?;
+        self.len -= 1;
+        self.entries.swap(index, self.len);
+        self.entries[self.len].take().map(|(_, v)| v)
+    }
+
+    /// Obtain the entry for a key, for in-place manipulation.
+    pub fn entry(&mut self, key: H64) -> H64ArrayMapEntry<'_, V, N>
+    {
+        let index = self.position(key);
+        H64ArrayMapEntry { map: self, key, index }
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self)
+    {
+        for entry in &mut self.entries[.. self.len] {
+            *entry = None;
+        }
+        self.len = 0;
+    }
+
+    /// Iterate over the entries in insertion order,
+    /// except that removals move the last entry.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, &V)>
+    {
+        self.entries[.. self.len].iter().flatten().map(|(k, v)| (*k, v))
+    }
+
+    /// Iterate over the entries in insertion order,
+    /// except that removals move the last entry.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (H64, &mut V)>
+    {
+        self.entries[.. self.len].iter_mut().flatten().map(|(k, v)| (*k, v))
+    }
+}
+
+impl<V, const N: usize> Default for H64ArrayMap<V, N>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl<V: fmt::Debug, const N: usize> fmt::Debug for H64ArrayMap<V, N>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Entry in a [`H64ArrayMap`], which may or may not be occupied.
+///
+/// See [`H64ArrayMap::entry`].
+pub struct H64ArrayMapEntry<'a, V, const N: usize>
+{
+    map: &'a mut H64ArrayMap<V, N>,
+    key: H64,
+    index: Option<usize>,
+}
+
+impl<'a, V, const N: usize> H64ArrayMapEntry<'a, V, N>
+{
+    /// The key of the entry.
+    #[inline(always)]
+    pub fn key(&self) -> H64
+    {
+        self.key
+    }
+
+    /// Obtain the value, if the entry is occupied.
+    pub fn get(&self) -> Option<&V>
+    {
+        let index = self.index?;
+        self.map.entries[index].as_ref().map(|(_, v)| v)
+    }
+
+    /// Modify the value, if the entry is occupied.
+    pub fn and_modify(self, f: impl FnOnce(&mut V)) -> Self
+    {
+        if let Some(index) = self.index {
+            if let Some((_, v)) = &mut self.map.entries[index] {
+                f(v);
+            }
+        }
+        self
+    }
+
+    /// Obtain the value, inserting `default` if the entry is vacant.
+    ///
+    /// Returns an error if the entry is vacant and the map is full.
+    pub fn or_insert(self, default: V) -> Result<&'a mut V, CapacityError<V>>
+    {
+        self.or_insert_with(|| default)
+    }
+
+    /// Obtain the value, inserting the result of `default` if the entry is vacant.
+    ///
+    /// Returns an error if the entry is vacant and the map is full.
+    /// `default` is called even then, so that the error can hold its result.
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> Result<&'a mut V, CapacityError<V>>
+    {
+        let index = match self.index {
+            Some(index) => index,
+            None => {
+                let value = default();
+                if self.map.len == N {
+                    return Err(CapacityError::new(value));
+                }
+                self.map.entries[self.map.len] = Some((self.key, value));
+                self.map.len += 1;
+                self.map.len - 1
+            },
+        };
+        match &mut self.map.entries[index] {
+            Some((_, v)) => Ok(v),
+            None => unreachable!("entries below len are occupied"),
+        }
+    }
+
+    /// Remove the entry, returning its value if it was occupied.
+    pub fn remove(self) -> Option<V>
+    {
+        self.map.remove(self.key)
+    }
+}
+
+/// Map with [`H64`] keys that stores up to `N` entries inline.
+///
+/// Small maps are [`H64ArrayMap`]s and do not allocate.
+/// Once an insertion would exceed `N` entries,
+/// the entries move to a [`H64HashMap`], where they stay.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct H64SmallMap<V, const N: usize>(SmallMapRepr<V, N>);
+
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+enum SmallMapRepr<V, const N: usize>
+{
+    Inline(H64ArrayMap<V, N>),
+    Heap(H64HashMap<H64, V>),
+}
+
+#[cfg(feature = "alloc")]
+impl<V, const N: usize> H64SmallMap<V, N>
+{
+    /// Create an empty map.
+    #[inline]
+    pub const fn new() -> Self
+    {
+        Self(SmallMapRepr::Inline(H64ArrayMap::new()))
+    }
+
+    /// Whether the entries have moved to a [`H64HashMap`].
+    #[inline]
+    pub fn is_spilled(&self) -> bool
+    {
+        matches!(self.0, SmallMapRepr::Heap(_))
+    }
+
+    /// The number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        match &self.0 {
+            SmallMapRepr::Inline(map) => map.len(),
+            SmallMapRepr::Heap(map) => map.len(),
+        }
+    }
+
+    /// Whether the map has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub fn get(&self, key: H64) -> Option<&V>
+    {
+        match &self.0 {
+            SmallMapRepr::Inline(map) => map.get(key),
+            SmallMapRepr::Heap(map) => map.get(&key),
+        }
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub fn get_mut(&mut self, key: H64) -> Option<&mut V>
+    {
+        match &mut self.0 {
+            SmallMapRepr::Inline(map) => map.get_mut(key),
+            SmallMapRepr::Heap(map) => map.get_mut(&key),
+        }
+    }
+
+    /// Whether a value is stored under a key.
+    #[inline]
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.get(key).is_some()
+    }
+
+    /// Insert an entry, returning the value previously stored under the key.
+    pub fn insert(&mut self, key: H64, value: V) -> Option<V>
+    {
+        match &mut self.0 {
+            SmallMapRepr::Inline(map) => match map.insert(key, value) {
+                Ok(old) => old,
+                Err(error) => {
+                    let mut heap = H64HashMap::with_capacity_and_hasher(2 * N, Default::default());
+                    heap.extend(map.entries.iter_mut().filter_map(Option::take));
+                    heap.extend([error.into_inner()]);
+                    self.0 = SmallMapRepr::Heap(heap);
+                    None
+                },
+            },
+            SmallMapRepr::Heap(map) => map.insert(key, value),
+        }
+    }
+
+    /// Remove an entry, returning its value.
+    #[inline]
+    pub fn remove(&mut self, key: H64) -> Option<V>
+    {
+        match &mut self.0 {
+            SmallMapRepr::Inline(map) => map.remove(key),
+            SmallMapRepr::Heap(map) => map.remove(&key),
+        }
+    }
+
+    /// Iterate over the entries in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, &V)>
+    {
+        let (inline, heap) = match &self.0 {
+            SmallMapRepr::Inline(map) => (Some(map), None),
+            SmallMapRepr::Heap(map) => (None, Some(map)),
+        };
+        Iterator::chain(
+            inline.into_iter().flat_map(|map| map.iter()),
+            heap.into_iter().flat_map(|map| map.iter().map(|(k, v)| (*k, v))),
+        )
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V, const N: usize> Default for H64SmallMap<V, N>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: fmt::Debug, const N: usize> fmt::Debug for H64SmallMap<V, N>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Map with [`H64`] keys stored in a [`Vec`] sorted by hashed value.
+///
+/// Lookups are binary searches and insertions and removals take linear time,
+/// so this suits maps that are built once and queried often.
+/// It uses less memory than a [`H64HashMap`]
+/// and can iterate over ranges of hashed values (see [`H64::into_raw`]).
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct H64VecMap<V>(Vec<(H64, V)>);
+
+#[cfg(feature = "alloc")]
+impl<V> H64VecMap<V>
+{
+    /// Create an empty map.
+    #[inline]
+    pub const fn new() -> Self
+    {
+        Self(Vec::new())
+    }
+
+    /// Create a map from entries sorted by hashed key.
+    ///
+    /// Panics if the entries are not sorted or contain duplicate keys.
+    pub fn from_sorted(entries: Vec<(H64, V)>) -> Self
+    {
+        assert!(
+            entries.windows(2).all(|w| w[0].0.0 < w[1].0.0),
+            "entries must be sorted by hashed key without duplicates",
+        );
+        Self(entries)
+    }
+
+    /// The number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.0.len()
+    }
+
+    /// Whether the map has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.0.is_empty()
+    }
+
+    fn search(&self, key: H64) -> Result<usize, usize>
+    {
+        self.0.binary_search_by_key(&key.0, |(k, _)| k.0)
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub fn get(&self, key: H64) -> Option<&V>
+    {
+        self.search(key).ok().map(|i| &self.0[i].1)
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub fn get_mut(&mut self, key: H64) -> Option<&mut V>
+    {
+        self.search(key).ok().map(|i| &mut self.0[i].1)
+    }
+
+    /// Whether a value is stored under a key.
+    #[inline]
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.search(key).is_ok()
+    }
+
+    /// Insert an entry, returning the value previously stored under the key.
+    pub fn insert(&mut self, key: H64, value: V) -> Option<V>
+    {
+        match self.search(key) {
+            Ok(i) => Some(mem::replace(&mut self.0[i].1, value)),
+            Err(i) => { self.0.insert(i, (key, value)); None },
+        }
+    }
+
+    /// Remove an entry, returning its value.
+    pub fn remove(&mut self, key: H64) -> Option<V>
+    {
+        self.search(key).ok().map(|i| self.0.remove(i).1)
+    }
+
+    /// Iterate over the entries in order of hashed key.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (H64, &V)> + ExactSizeIterator
+    {
+        self.0.iter().map(|(k, v)| (*k, v))
+    }
+
+    /// Iterate over the entries whose hashed keys are in a range,
+    /// in order of hashed key.
+    pub fn range(&self, hashed: impl RangeBounds<u64>) -> impl DoubleEndedIterator<Item = (H64, &V)>
+    {
+        let start = match hashed.start_bound() {
+            Bound::Included(&h) => self.0.partition_point(|(k, _)| k.0 < h),
+            Bound::Excluded(&h) => self.0.partition_point(|(k, _)| k.0 <= h),
+            Bound::Unbounded => 0,
+        };
+        let end = match hashed.end_bound() {
+            Bound::Included(&h) => self.0.partition_point(|(k, _)| k.0 <= h),
+            Bound::Excluded(&h) => self.0.partition_point(|(k, _)| k.0 < h),
+            Bound::Unbounded => self.0.len(),
+        };
+        self.0[start .. end.max(start)].iter().map(|(k, v)| (*k, v))
+    }
+
+    /// The entries, sorted by hashed key.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[(H64, V)]
+    {
+        &self.0
+    }
+
+    /// The entries, sorted by hashed key.
+    #[inline(always)]
+    pub fn into_vec(self) -> Vec<(H64, V)>
+    {
+        self.0
+    }
+}
+
+/// Later entries replace earlier ones with the same key.
+#[cfg(feature = "alloc")]
+impl<V> FromIterator<(H64, V)> for H64VecMap<V>
+{
+    fn from_iter<I: IntoIterator<Item = (H64, V)>>(iter: I) -> Self
+    {
+        let mut entries: Vec<(H64, V)> = iter.into_iter().collect();
+        entries.sort_by_key(|(k, _)| k.0);
+        entries.dedup_by(|later, earlier| {
+            let duplicate = later.0 == earlier.0;
+            if duplicate {
+                mem::swap(later, earlier);
+            }
+            duplicate
+        });
+        Self(entries)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: fmt::Debug> fmt::Debug for H64VecMap<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Immutable map with [`H64`] keys and single-probe lookups.
+///
+/// Building the map computes a minimal perfect hash function
+/// using the compress, hash, and displace algorithm:
+/// keys are grouped into buckets of about [`LAMBDA`] keys,
+/// and each bucket is given a displacement that sends its keys to free slots.
+/// Lookups hash the key, read its bucket's displacement,
+/// and compare against the one entry in the resulting slot.
+/// The entries are stored densely, without empty slots.
+///
+/// [`LAMBDA`]: `Self::LAMBDA`
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct H64FrozenMap<V>
+{
+    seed: u64,
+    displacements: Box<[(u32, u32)]>,
+    entries: Box<[(H64, V)]>,
+}
+
+#[cfg(feature = "alloc")]
+impl<V> H64FrozenMap<V>
+{
+    /// Average number of keys per bucket.
+    ///
+    /// More keys per bucket use less memory but take longer to build.
+    pub const LAMBDA: usize = frozen::LAMBDA;
+
+    /// Build a map from entries.
+    ///
+    /// Later entries replace earlier ones with the same key.
+    pub fn new(entries: Vec<(H64, V)>) -> Self
+    {
+        let H64VecMap(entries) = entries.into_iter().collect();
+        let keys: Vec<H64> = entries.iter().map(|&(k, _)| k).collect();
+        let (seed, table) = (0 ..)
+            .find_map(|attempt| {
+                let seed = H64::hash(attempt).0;
+                Some((seed, frozen::build(&keys, seed)?))
+            })
+            .expect("some seed works");
+
+        // Move each entry into its slot.
+        let mut entries: Vec<Option<(H64, V)>> = entries.into_iter().map(Some).collect();
+        let entries = table.slots.iter()
+            .map(|&i| entries[i].take().expect("slots are distinct"))
+            .collect();
+
+        Self { seed, displacements: table.displacements.into(), entries }
+    }
+
+    /// The number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    /// Whether the map has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.entries.is_empty()
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub fn get(&self, key: H64) -> Option<&V>
+    {
+        let slot = frozen::slot(self.seed, &self.displacements, self.entries.len(), key)?;
+        let (k, v) = &self.entries[slot];
+        (*k == key).then_some(v)
+    }
+
+    /// Whether a value is stored under a key.
+    #[inline]
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.get(key).is_some()
+    }
+
+    /// Iterate over the entries in arbitrary order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (H64, &V)>
+    {
+        self.entries.iter().map(|(k, v)| (*k, v))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> FromIterator<(H64, V)> for H64FrozenMap<V>
+{
+    fn from_iter<I: IntoIterator<Item = (H64, V)>>(iter: I) -> Self
+    {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: fmt::Debug> fmt::Debug for H64FrozenMap<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Immutable `'static` map with [`H64`] keys and single-probe lookups.
+///
+/// Like [`H64FrozenMap`], but built at compile time by [`h64_static_map!`].
+/// Lookups go through one more indirection,
+/// because the values stay in the order in which they were given.
+pub struct H64StaticMap<V: 'static>
+{
+    seed: u64,
+    displacements: &'static [(u32, u32)],
+    slots: &'static [(H64, usize)],
+    values: &'static [V],
+}
+
+impl<V: 'static> H64StaticMap<V>
+{
+    /// Create a map from a table and the values of its keys.
+    ///
+    /// The values must be in the order of the keys given to [`H64StaticTable::new`].
+    /// Use [`h64_static_map!`] instead of calling this directly.
+    #[inline]
+    pub const fn from_table<const N: usize, const B: usize>(
+        table: &'static H64StaticTable<N, B>,
+        values: &'static [V; N],
+    ) -> Self
+    {
+        Self {
+            seed: table.seed,
+            displacements: &table.displacements,
+            slots: &table.slots,
+            values,
+        }
+    }
+
+    /// The number of entries in the map.
+    #[inline(always)]
+    pub const fn len(&self) -> usize
+    {
+        self.values.len()
+    }
+
+    /// Whether the map has no entries.
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool
+    {
+        self.values.is_empty()
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub const fn get(&self, key: H64) -> Option<&'static V>
+    {
+        let Some(slot) = frozen::slot(self.seed, self.displacements, self.slots.len(), key)
+            else { return None };
+        let (k, i) = self.slots[slot];
+        if k.0 == key.0 { Some(&self.values[i]) } else { None }
+    }
+
+    /// Whether a value is stored under a key.
+    #[inline]
+    pub const fn contains_key(&self, key: H64) -> bool
+    {
+        self.get(key).is_some()
+    }
+
+    /// Iterate over the entries in arbitrary order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (H64, &'static V)>
+    {
+        let values = self.values;
+        self.slots.iter().map(move |&(k, i)| (k, &values[i]))
+    }
+}
+
+impl<V: 'static> Clone for H64StaticMap<V>
+{
+    #[inline(always)]
+    fn clone(&self) -> Self
+    {
+        *self
+    }
+}
+
+impl<V: 'static> Copy for H64StaticMap<V>
+{
+}
+
+impl<V: fmt::Debug + 'static> fmt::Debug for H64StaticMap<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Perfect hash function for a [`H64StaticMap`] with `N` keys and `B` buckets.
+///
+/// Use [`h64_static_map!`] instead of using this directly.
+pub struct H64StaticTable<const N: usize, const B: usize>
+{
+    seed: u64,
+    displacements: [(u32, u32); B],
+    /// Key in each slot, and its index in the original order.
+    slots: [(H64, usize); N],
+}
+
+impl<const N: usize, const B: usize> H64StaticTable<N, B>
+{
+    /// Number of buckets for a table of `len` keys.
+    #[doc(hidden)]
+    pub const fn buckets_for(len: usize) -> usize
+    {
+        frozen::buckets(len)
+    }
+
+    /// Compute the perfect hash function for the hashes of the given [`u64`]s.
+    ///
+    /// Panics if there are not `N` keys, if `B` is not [`buckets_for(N)`],
+    /// or if there are duplicate keys.
+    ///
+    /// [`buckets_for(N)`]: `Self::buckets_for`
+    pub const fn new(keys: &[u64]) -> Self
+    {
+        assert!(keys.len() == N, "wrong number of keys");
+        assert!(B == frozen::buckets(N), "wrong number of buckets");
+
+        let mut hashed = [H64(0); N];
+        let mut i = 0;
+        while i < N {
+            hashed[i] = H64::hash(keys[i]);
+            let mut j = 0;
+            while j < i {
+                assert!(hashed[j].0 != hashed[i].0, "duplicate key");
+                j += 1;
+            }
+            i += 1;
+        }
+
+        let mut attempt = 0;
+        loop {
+            if let Some(table) = Self::try_new(&hashed, H64::hash(attempt).0) {
+                return table;
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Compile-time version of [`frozen::build`].
+    const fn try_new(keys: &[H64; N], seed: u64) -> Option<Self>
+    {
+        // Sort the keys by bucket, with a counting sort.
+        let mut hashes = [(0, 0, 0); N];
+        let mut starts = [0; B];
+        let mut i = 0;
+        while i < N {
+            hashes[i] = frozen::hashes(keys[i], seed);
+            starts[hashes[i].0 as usize % B] += 1;
+            i += 1;
+        }
+        let mut max_size = 0;
+        let mut sum = 0;
+        let mut b = 0;
+        while b < B {
+            if starts[b] > max_size {
+                max_size = starts[b];
+            }
+            sum += starts[b];
+            starts[b] = sum - starts[b];
+            b += 1;
+        }
+        let mut members = [0; N];
+        let mut ends = starts;
+        let mut i = 0;
+        while i < N {
+            let b = hashes[i].0 as usize % B;
+            members[ends[b]] = i;
+            ends[b] += 1;
+            i += 1;
+        }
+
+        let mut displacements = [(0, 0); B];
+        let mut slots = [(H64(0), usize::MAX); N];
+        let mut tried = [0u64; N];
+        let mut generation = 0;
+
+        // Place the largest buckets first, while there are many free slots.
+        let mut size = max_size;
+        while size > 0 {
+            let mut b = 0;
+            while b < B {
+                if ends[b] - starts[b] == size {
+                    let Some(displacement) = Self::find_displacement(
+                        &hashes, &members, starts[b], ends[b], &slots, &mut tried, &mut generation,
+                    ) else {
+                        return None;
+                    };
+                    displacements[b] = displacement;
+                    let mut m = starts[b];
+                    while m < ends[b] {
+                        let (_, f1, f2) = hashes[members[m]];
+                        slots[frozen::displace(f1, f2, displacement, N)] = (keys[members[m]], members[m]);
+                        m += 1;
+                    }
+                }
+                b += 1;
+            }
+            size -= 1;
+        }
+
+        Some(Self { seed, displacements, slots })
+    }
+
+    /// Find a displacement that sends the given bucket members to distinct free slots.
+    const fn find_displacement(
+        hashes: &[(u32, u32, u32); N],
+        members: &[usize; N],
+        start: usize,
+        end: usize,
+        slots: &[(H64, usize); N],
+        tried: &mut [u64; N],
+        generation: &mut u64,
+    ) -> Option<(u32, u32)>
+    {
+        let mut d1 = 0;
+        while d1 < N as u32 {
+            let mut d2 = 0;
+            'displacements: while d2 < N as u32 {
+                *generation += 1;
+                let mut m = start;
+                while m < end {
+                    let (_, f1, f2) = hashes[members[m]];
+                    let slot = frozen::displace(f1, f2, (d1, d2), N);
+                    if slots[slot].1 != usize::MAX || tried[slot] == *generation {
+                        d2 += 1;
+                        continue 'displacements;
+                    }
+                    tried[slot] = *generation;
+                    m += 1;
+                }
+                return Some((d1, d2));
+            }
+            d1 += 1;
+        }
+        None
+    }
+}
+
+mod frozen
+{
+    //! Minimal perfect hashing for [`H64FrozenMap`] and [`H64StaticMap`].
+
+    use super::*;
+
+    /// Compute the bucket and the two displacement factors of a key.
+    #[inline]
+    pub const fn hashes(key: H64, seed: u64) -> (u32, u32, u32)
+    {
+        let a = H64::hash(key.0 ^ seed).0;
+        let b = H64::hash(a).0;
+        ((a >> 32) as u32, a as u32, b as u32)
+    }
+
+    /// Compute the slot of a key from its hashes and its bucket's displacement.
+    #[inline]
+    pub const fn displace(f1: u32, f2: u32, (d1, d2): (u32, u32), len: usize) -> usize
+    {
+        let i = u32::wrapping_add(u32::wrapping_mul(f1, d1), u32::wrapping_add(f2, d2));
+        i as usize % len
+    }
+
+    /// Average number of keys per bucket.
+    pub const LAMBDA: usize = 5;
+
+    /// Number of buckets for a table of `len` keys.
+    #[inline]
+    pub const fn buckets(len: usize) -> usize
+    {
+        if len <= LAMBDA { 1 } else { len.div_ceil(LAMBDA) }
+    }
+
+    /// Find the slot of a key in a table of `len` slots.
+    ///
+    /// If the key is in the table, it is in this slot.
+    /// Returns [`None`] if the table is empty.
+    #[inline]
+    pub const fn slot(seed: u64, displacements: &[(u32, u32)], len: usize, key: H64) -> Option<usize>
+    {
+        if len == 0 {
+            return None;
+        }
+        let (g, f1, f2) = hashes(key, seed);
+        let displacement = displacements[g as usize % displacements.len()];
+        Some(displace(f1, f2, displacement, len))
+    }
+
+    /// Find a displacement for each bucket such that all keys go to distinct slots.
+    ///
+    /// Returns [`None`] if this seed does not work.
+    /// The keys must be distinct.
+    #[cfg(feature = "alloc")]
+    pub fn build(keys: &[H64], seed: u64) -> Option<Table>
+    {
+        let len = keys.len();
+        let buckets_len = buckets(len);
+        let hashes: Vec<_> = keys.iter().map(|&key| hashes(key, seed)).collect();
+
+        let mut buckets = vec![Vec::new(); buckets_len];
+        for (i, &(g, _, _)) in hashes.iter().enumerate() {
+            buckets[g as usize % buckets_len].push(i);
+        }
+
+        // Place the largest buckets first, while there are many free slots.
+        let mut order: Vec<usize> = (0 .. buckets_len).collect();
+        order.sort_by_key(|&b| usize::MAX - buckets[b].len());
+
+        let mut displacements = vec![(0, 0); buckets_len];
+        let mut slots: Vec<Option<usize>> = vec![None; len];
+        let mut tried = vec![0u64; len];
+        let mut generation = 0;
+        let mut placed = Vec::new();
+
+        'buckets: for b in order {
+            for d1 in 0 .. len as u32 {
+                'displacements: for d2 in 0 .. len as u32 {
+                    generation += 1;
+                    placed.clear();
+                    for &i in &buckets[b] {
+                        let (_, f1, f2) = hashes[i];
+                        let slot = displace(f1, f2, (d1, d2), len);
+                        if slots[slot].is_some() || tried[slot] == generation {
+                            continue 'displacements;
+                        }
+                        tried[slot] = generation;
+                        placed.push((slot, i));
+                    }
+                    displacements[b] = (d1, d2);
+                    for &(slot, i) in &placed {
+                        slots[slot] = Some(i);
+                    }
+                    continue 'buckets;
+                }
+            }
+            if !buckets[b].is_empty() {
+                return None;
+            }
+        }
+
+        let slots = slots.into_iter().map(|i| i.expect("every slot is filled")).collect();
+        Some(Table { displacements, slots })
+    }
+
+    /// Result of [`build`].
+    #[cfg(feature = "alloc")]
+    pub struct Table
+    {
+        /// Displacement of each bucket.
+        pub displacements: Vec<(u32, u32)>,
+
+        /// Index of the key in each slot.
+        pub slots: Vec<usize>,
+    }
+}
+
+/// Map with [`H64`] keys, indexed directly by original [`u64`].
+///
+/// This suits keys from an [`H64Allocator`], whose original values are dense.
+/// Lookups index a [`Vec`] without hashing or probing,
+/// but the [`Vec`] is as long as the largest original key,
+/// so sparse keys waste memory.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default)]
+pub struct H64DenseMap<V>
+{
+    slots: Vec<Option<V>>,
+    len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<V> H64DenseMap<V>
+{
+    /// Create an empty map.
+    #[inline]
+    pub const fn new() -> Self
+    {
+        Self { slots: Vec::new(), len: 0 }
+    }
+
+    /// Create an empty map with room for keys whose original values are less than `capacity`.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self
+    {
+        Self { slots: Vec::with_capacity(capacity), len: 0 }
+    }
+
+    /// The number of entries in the map.
+    #[inline(always)]
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    /// Whether the map has no entries.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    #[inline]
+    fn index(key: H64) -> Option<usize>
+    {
+        usize::try_from(key.unhash()).ok()
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub fn get(&self, key: H64) -> Option<&V>
+    {
+        self.slots.get(Self::index(key)?)?.as_ref()
+    }
+
+    /// Obtain the value stored under a key.
+    #[inline]
+    pub fn get_mut(&mut self, key: H64) -> Option<&mut V>
+    {
+        self.slots.get_mut(Self::index(key)?)?.as_mut()
+    }
+
+    /// Whether a value is stored under a key.
+    #[inline]
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.get(key).is_some()
+    }
+
+    /// Insert an entry, returning the value previously stored under the key.
+    ///
+    /// This grows the map to the original value of the key.
+    /// Panics if the original value does not fit in a [`usize`].
+    pub fn insert(&mut self, key: H64, value: V) -> Option<V>
+    {
+        let index = Self::index(key).expect("H64DenseMap key too large");
+        if index >= self.slots.len() {
+            let len = index.checked_add(1).expect("H64DenseMap key too large");
+            self.slots.resize_with(len, || None);
+        }
+        let old = self.slots[index].replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Remove an entry, returning its value.
+    pub fn remove(&mut self, key: H64) -> Option<V>
+    {
+        let old = self.slots.get_mut(Self::index(key)?)?.take();
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self)
+    {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    /// Iterate over the entries in order of original key.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (H64, &V)>
+    {
+        self.slots.iter().enumerate()
+            .filter_map(|(i, slot)| Some((H64::hash(i as u64), slot.as_ref()?)))
+    }
+
+    /// Iterate over the entries in order of original key.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = (H64, &mut V)>
+    {
+        self.slots.iter_mut().enumerate()
+            .filter_map(|(i, slot)| Some((H64::hash(i as u64), slot.as_mut()?)))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: PartialEq> PartialEq for H64DenseMap<V>
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        // Trailing empty slots do not affect the contents.
+        let (short, long) =
+            if self.slots.len() <= other.slots.len() { (self, other) } else { (other, self) };
+        self.len == other.len
+            && long.slots[.. short.slots.len()] == short.slots[..]
+            && long.slots[short.slots.len() ..].iter().all(Option::is_none)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: Eq> Eq for H64DenseMap<V>
+{
+}
+
+#[cfg(feature = "alloc")]
+impl<V> Extend<(H64, V)> for H64DenseMap<V>
+{
+    fn extend<I: IntoIterator<Item = (H64, V)>>(&mut self, iter: I)
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> FromIterator<(H64, V)> for H64DenseMap<V>
+{
+    fn from_iter<I: IntoIterator<Item = (H64, V)>>(iter: I) -> Self
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: fmt::Debug> fmt::Debug for H64DenseMap<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Set of [`H64`]s, stored as a bit per original [`u64`].
+///
+/// Like [`H64DenseMap`], this suits [`H64`]s from an [`H64Allocator`].
+/// The set is as large as the largest original [`u64`] divided by eight,
+/// and set operations work on 64 elements at a time.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default)]
+pub struct H64BitSet(Vec<u64>);
+
+#[cfg(feature = "alloc")]
+impl H64BitSet
+{
+    /// Create an empty set.
+    #[inline]
+    pub const fn new() -> Self
+    {
+        Self(Vec::new())
+    }
+
+    #[inline]
+    fn position(h64: H64) -> Option<(usize, u64)>
+    {
+        let i = h64.unhash();
+        Some((usize::try_from(i / 64).ok()?, 1 << (i % 64)))
+    }
+
+    /// The number of elements in the set.
+    ///
+    /// This takes time linear in the size of the set.
+    pub fn len(&self) -> usize
+    {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Whether the set has no elements.
+    pub fn is_empty(&self) -> bool
+    {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    /// Insert an element, returning whether it was not yet in the set.
+    ///
+    /// This grows the set to the original value of the element.
+    /// Panics if the original value divided by 64 does not fit in a [`usize`].
+    pub fn insert(&mut self, h64: H64) -> bool
+    {
+        let (word, bit) = Self::position(h64).expect("H64BitSet element too large");
+        if word >= self.0.len() {
+            self.0.resize(word + 1, 0);
+        }
+        let absent = self.0[word] & bit == 0;
+        self.0[word] |= bit;
+        absent
+    }
+
+    /// Whether an element is in the set.
+    #[inline]
+    pub fn contains(&self, h64: H64) -> bool
+    {
+        let Some((word, bit)) = Self::position(h64) else { return false };
+        self.0.get(word).is_some_and(|w| w & bit != 0)
+    }
+
+    /// Remove an element, returning whether it was in the set.
+    pub fn remove(&mut self, h64: H64) -> bool
+    {
+        let Some((word, bit)) = Self::position(h64) else { return false };
+        let Some(w) = self.0.get_mut(word) else { return false };
+        let present = *w & bit != 0;
+        *w &= !bit;
+        present
+    }
+
+    /// Remove all elements.
+    pub fn clear(&mut self)
+    {
+        self.0.clear();
+    }
+
+    /// Add the elements of another set to this one.
+    pub fn union_with(&mut self, other: &Self)
+    {
+        if other.0.len() > self.0.len() {
+            self.0.resize(other.0.len(), 0);
+        }
+        for (a, b) in Iterator::zip(self.0.iter_mut(), &other.0) {
+            *a |= b;
+        }
+    }
+
+    /// Remove the elements that are not in another set.
+    pub fn intersect_with(&mut self, other: &Self)
+    {
+        self.0.truncate(other.0.len());
+        for (a, b) in Iterator::zip(self.0.iter_mut(), &other.0) {
+            *a &= b;
+        }
+    }
+
+    /// Remove the elements that are in another set.
+    pub fn difference_with(&mut self, other: &Self)
+    {
+        for (a, b) in Iterator::zip(self.0.iter_mut(), &other.0) {
+            *a &= !b;
+        }
+    }
+
+    /// Whether all elements of this set are in another set.
+    pub fn is_subset(&self, other: &Self) -> bool
+    {
+        self.0.iter().enumerate()
+            .all(|(i, a)| a & !other.0.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    /// Iterate over the elements in order of original [`u64`].
+    pub fn iter(&self) -> impl Iterator<Item = H64> + '_
+    {
+        self.0.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            core::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros();
+                word &= word - 1;
+                Some(H64::hash(i as u64 * 64 + u64::from(bit)))
+            })
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq for H64BitSet
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        // Trailing zero words do not affect the contents.
+        let (short, long) = if self.0.len() <= other.0.len() { (self, other) } else { (other, self) };
+        long.0[.. short.0.len()] == short.0[..]
+            && long.0[short.0.len() ..].iter().all(|&word| word == 0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Eq for H64BitSet
+{
+}
+
+#[cfg(feature = "alloc")]
+impl Extend<H64> for H64BitSet
+{
+    fn extend<I: IntoIterator<Item = H64>>(&mut self, iter: I)
+    {
+        for h64 in iter {
+            self.insert(h64);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<H64> for H64BitSet
+{
+    fn from_iter<I: IntoIterator<Item = H64>>(iter: I) -> Self
+    {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64BitSet
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Read-only map with [`H64`] keys and byte string values, stored in a byte buffer.
+///
+/// The buffer is typically a memory-mapped file written by [`write`].
+/// Lookups binary search the buffer directly, without deserializing it.
+/// The format is, with all integers little-endian [`u64`]s:
+///
+/// 1. The magic bytes [`MAGIC`].
+/// 2. The number of entries _n_.
+/// 3. The _n_ hashed keys, in ascending order.
+/// 4. _n_ + 1 offsets into the values, starting at zero.
+/// 5. The concatenated values.
+///
+/// [`new`] checks only the sizes, in constant time.
+/// Lookups in a corrupt buffer return wrong results or [`None`], but never panic.
+/// Use [`validate`] to check the whole buffer.
+///
+/// [`MAGIC`]: `Self::MAGIC`
+/// [`new`]: `Self::new`
+/// [`validate`]: `Self::validate`
+/// [`write`]: `Self::write`
+#[derive(Clone, Copy)]
+pub struct H64MmapMap<'a>
+{
+    len: usize,
+    keys: &'a [u8],
+    offsets: &'a [u8],
+    values: &'a [u8],
+}
+
+impl<'a> H64MmapMap<'a>
+{
+    /// Bytes at the start of the format, including its version.
+    pub const MAGIC: [u8; 8] = *b"H64MAP01";
+
+    /// Interpret a buffer in the format.
+    ///
+    /// Returns an error if the buffer is too small for its number of entries.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, H64MmapError>
+    {
+        let (header, rest) = bytes.split_at_checked(16).ok_or(H64MmapError("truncated header"))?;
+        if header[.. 8] != Self::MAGIC {
+            return Err(H64MmapError("wrong magic bytes"));
+        }
+        let len = usize::try_from(read_u64(header, 1)).map_err(|_| H64MmapError("too many entries"))?;
+        let keys_size = len.checked_mul(8).ok_or(H64MmapError("too many entries"))?;
+        let (keys, rest) = rest.split_at_checked(keys_size).ok_or(H64MmapError("truncated keys"))?;
+        let (offsets, values) = rest.split_at_checked(keys_size + 8)
+            .ok_or(H64MmapError("truncated offsets"))?;
+        if read_u64(offsets, len) != values.len() as u64 {
+            return Err(H64MmapError("wrong size of values"));
+        }
+        Ok(Self { len, keys, offsets, values })
+    }
+
+    /// Check that the keys are sorted and the offsets are in bounds.
+    ///
+    /// This takes linear time.
+    pub fn validate(&self) -> Result<(), H64MmapError>
+    {
+        if (1 .. self.len).any(|i| self.key(i - 1) >= self.key(i)) {
+            return Err(H64MmapError("keys not sorted"));
+        }
+        if read_u64(self.offsets, 0) != 0 {
+            return Err(H64MmapError("offsets do not start at zero"));
+        }
+        if (0 .. self.len).any(|i| read_u64(self.offsets, i) > read_u64(self.offsets, i + 1)) {
+            return Err(H64MmapError("offsets not sorted"));
+        }
+        Ok(())
+    }
+
+    /// The number of entries in the map.
+    #[inline(always)]
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    /// Whether the map has no entries.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    /// Hashed key of the `i`th entry.
+    #[inline]
+    fn key(&self, i: usize) -> u64
+    {
+        read_u64(self.keys, i)
+    }
+
+    /// Value of the `i`th entry, or [`None`] if its offsets are corrupt.
+    fn value(&self, i: usize) -> Option<&'a [u8]>
+    {
+        let start = usize::try_from(read_u64(self.offsets, i)).ok()?;
+        let end = usize::try_from(read_u64(self.offsets, i + 1)).ok()?;
+        self.values.get(start .. end)
+    }
+
+    /// Obtain the value stored under a key.
+    pub fn get(&self, key: H64) -> Option<&'a [u8]>
+    {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.key(mid).cmp(&key.0) {
+                core::cmp::Ordering::Less => low = mid + 1,
+                core::cmp::Ordering::Greater => high = mid,
+                core::cmp::Ordering::Equal => return self.value(mid),
+            }
+        }
+        None
+    }
+
+    /// Whether a value is stored under a key.
+    #[inline]
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.get(key).is_some()
+    }
+
+    /// Iterate over the entries in order of hashed key.
+    ///
+    /// Entries with corrupt offsets are skipped.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, &'a [u8])> + '_
+    {
+        (0 .. self.len).filter_map(|i| Some((H64(self.key(i)), self.value(i)?)))
+    }
+
+    /// Write entries in the format.
+    ///
+    /// Later entries replace earlier ones with the same key.
+    #[cfg(feature = "std")]
+    pub fn write<V: AsRef<[u8]>>(
+        mut writer: impl std::io::Write,
+        entries: impl IntoIterator<Item = (H64, V)>,
+    ) -> std::io::Result<()>
+    {
+        let map: H64VecMap<V> = entries.into_iter().collect();
+        writer.write_all(&Self::MAGIC)?;
+        writer.write_all(&(map.len() as u64).to_le_bytes())?;
+        for (key, _) in map.iter() {
+            writer.write_all(&key.0.to_le_bytes())?;
+        }
+        let mut offset = 0u64;
+        writer.write_all(&offset.to_le_bytes())?;
+        for (_, value) in map.iter() {
+            offset += value.as_ref().len() as u64;
+            writer.write_all(&offset.to_le_bytes())?;
+        }
+        for (_, value) in map.iter() {
+            writer.write_all(value.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for H64MmapMap<'_>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Read the `i`th little-endian [`u64`] from a buffer of at least `8 * (i + 1)` bytes.
+#[inline]
+fn read_u64(bytes: &[u8], i: usize) -> u64
+{
+    let mut buffer = [0; 8];
+    buffer.copy_from_slice(&bytes[8 * i .. 8 * i + 8]);
+    u64::from_le_bytes(buffer)
+}
+
+/// Error returned when a buffer is not a valid [`H64MmapMap`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct H64MmapError(&'static str);
+
+impl fmt::Display for H64MmapError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "invalid H64 map buffer: {}", self.0)
+    }
+}
+
+impl core::error::Error for H64MmapError
+{
+}
+
+/// Concurrent hash map made of [`RwLock`]ed [`H64HashMap`]s.
+///
+/// Each key belongs to one shard, selected by its hash,
+/// so threads accessing different shards do not contend.
+/// Locks are not poisoned, as no operation leaves a shard inconsistent.
+#[cfg(feature = "std")]
+pub struct H64ShardedMap<K, V>
+{
+    shards: Box<[RwLock<H64HashMap<K, V>>]>,
+    shift: u32,
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V> H64ShardedMap<K, V>
+{
+    /// Number of shards used by [`new`](Self::new).
+    pub const DEFAULT_SHARDS: usize = 16;
+
+    /// Create an empty map with [`DEFAULT_SHARDS`](Self::DEFAULT_SHARDS) shards.
+    pub fn new() -> Self
+    {
+        Self::with_shards(Self::DEFAULT_SHARDS)
+    }
+
+    /// Create an empty map with the given number of shards.
+    ///
+    /// Panics if `shards` is not a power of two.
+    pub fn with_shards(shards: usize) -> Self
+    {
+        assert!(shards.is_power_of_two(), "number of shards must be a power of two");
+        let shift = u64::BITS - shards.trailing_zeros();
+        let shards = (0 .. shards).map(|_| RwLock::default()).collect();
+        Self { shards, shift }
+    }
+
+    fn shard(&self, key: &K) -> &RwLock<H64HashMap<K, V>>
+    {
+        // The top 7 bits of the hash are used by the shards themselves
+        // to tell apart keys in the same group, so select by the bits below.
+        let hash = BuildHasherDefault::<H64Hasher>::default().hash_one(key);
+        let index = u64::checked_shr(hash << 7, self.shift).unwrap_or(0);
+        &self.shards[index as usize]
+    }
+
+    /// Obtain a clone of the value stored under a key.
+    pub fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let shard = self.shard(key).read().unwrap_or_else(PoisonError::into_inner);
+        shard.get(key).cloned()
+    }
+
+    /// Whether a value is stored under a key.
+    pub fn contains_key(&self, key: &K) -> bool
+    {
+        let shard = self.shard(key).read().unwrap_or_else(PoisonError::into_inner);
+        shard.contains_key(key)
+    }
+
+    /// Insert an entry, returning the value previously stored under the key.
+    pub fn insert(&self, key: K, value: V) -> Option<V>
+    {
+        let mut shard = self.shard(&key).write().unwrap_or_else(PoisonError::into_inner);
+        shard.insert(key, value)
+    }
+
+    /// Remove an entry, returning its value.
+    pub fn remove(&self, key: &K) -> Option<V>
+    {
+        let mut shard = self.shard(key).write().unwrap_or_else(PoisonError::into_inner);
+        shard.remove(key)
+    }
+
+    /// The number of entries in the map.
+    ///
+    /// Concurrent modifications may or may not be counted.
+    pub fn len(&self) -> usize
+    {
+        self.shards.iter()
+            .map(|shard| shard.read().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    /// Whether the map has no entries.
+    ///
+    /// Concurrent modifications may or may not be taken into account.
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    /// Call `f` on each entry, one shard at a time.
+    ///
+    /// Each shard is locked for reading while its entries are visited,
+    /// so `f` must not modify the map.
+    pub fn for_each(&self, mut f: impl FnMut(&K, &V))
+    {
+        for shard in self.shards.iter() {
+            let shard = shard.read().unwrap_or_else(PoisonError::into_inner);
+            for (k, v) in shard.iter() {
+                f(k, v);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V> Default for H64ShardedMap<K, V>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for H64ShardedMap<K, V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let mut map = f.debug_map();
+        for shard in self.shards.iter() {
+            let shard = shard.read().unwrap_or_else(PoisonError::into_inner);
+            map.entries(shard.iter());
+        }
+        map.finish()
+    }
+}
+
+/// Fixed-capacity set of [`H64`]s that can be used without locks.
+///
+/// Inserting and looking up take a bounded number of steps
+/// (linear probing over a table of [`AtomicU64`]s),
+/// so no thread ever waits for another.
+/// Elements cannot be removed.
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+pub struct H64AtomicSet
+{
+    // Zero marks empty slots, so the H64 with hash zero is stored separately.
+    slots: Box<[AtomicU64]>,
+    has_zero: AtomicBool,
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+impl H64AtomicSet
+{
+    /// Create an empty set with room for at least `capacity` elements.
+    ///
+    /// The capacity is rounded up to a power of two.
+    pub fn with_capacity(capacity: usize) -> Self
+    {
+        let slots = capacity.max(1).next_power_of_two();
+        let slots = (0 .. slots).map(|_| AtomicU64::new(0)).collect();
+        Self { slots, has_zero: AtomicBool::new(false) }
+    }
+
+    /// The number of elements the set has room for.
+    #[inline]
+    pub fn capacity(&self) -> usize
+    {
+        self.slots.len()
+    }
+
+    fn probe(&self, h64: H64) -> impl Iterator<Item = &AtomicU64>
+    {
+        let mask = self.slots.len() - 1;
+        let start = h64.0 as usize & mask;
+        (0 .. self.slots.len()).map(move |i| &self.slots[(start + i) & mask])
+    }
+
+    /// Insert an element, returning whether it was not yet in the set.
+    ///
+    /// Returns an error if the set is full.
+    pub fn insert(&self, h64: H64) -> Result<bool, CapacityError<H64>>
+    {
+        if h64.0 == 0 {
+            return Ok(!self.has_zero.swap(true, Ordering::AcqRel));
+        }
+        for slot in self.probe(h64) {
+            match slot.compare_exchange(0, h64.0, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return Ok(true),
+                Err(other) if other == h64.0 => return Ok(false),
+                Err(_) => continue,
+            }
+        }
+        Err(CapacityError::new(h64))
+    }
+
+    /// Whether an element is in the set.
+    pub fn contains(&self, h64: H64) -> bool
+    {
+        if h64.0 == 0 {
+            return self.has_zero.load(Ordering::Acquire);
+        }
+        for slot in self.probe(h64) {
+            match slot.load(Ordering::Acquire) {
+                0 => return false,
+                other if other == h64.0 => return true,
+                _ => continue,
+            }
+        }
+        false
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+impl fmt::Debug for H64AtomicSet
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let zero = self.has_zero.load(Ordering::Acquire).then_some(H64(0));
+        let others = self.slots.iter()
+            .map(|slot| H64(slot.load(Ordering::Acquire)))
+            .filter(|h64| h64.0 != 0);
+        f.debug_set().entries(zero).entries(others).finish()
+    }
+}
+
+/// Interner that maps values to [`H64`] handles.
+///
+/// Each distinct value is assigned the next [`H64`] from an [`H64Allocator`],
+/// and can be resolved from its handle.
+/// Values are stored once, shared between both directions.
+/// Values can be unsized, as in [`H64Interner`].
+#[cfg(feature = "std")]
+pub struct Interner<T: ?Sized>
+{
+    allocator: H64Allocator,
+    handles: HashMap<Arc<T>, H64, RandomState>,
+    values: H64HashMap<H64, Arc<T>>,
+}
+
+/// Interner that maps strings to [`H64`] handles.
+#[cfg(feature = "std")]
+pub type H64Interner = Interner<str>;
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + Hash + Eq> Interner<T>
+{
+    /// Create an empty interner.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self {
+            allocator: H64Allocator::new(),
+            handles: HashMap::default(),
+            values: H64HashMap::default(),
+        }
+    }
+
+    /// The number of interned values.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.values.len()
+    }
+
+    /// Whether no values have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.values.is_empty()
+    }
+
+    /// Intern a value, returning its handle.
+    ///
+    /// Interning an equal value again returns the same handle.
+    /// The value is converted to an [`Arc`] only if it is new.
+    pub fn intern<Q>(&mut self, value: Q) -> H64
+    where
+        Q: Borrow<T> + Into<Arc<T>>,
+    {
+        if let Some(&handle) = self.handles.get(value.borrow()) {
+            return handle;
+        }
+        let handle = self.allocator.alloc();
+        let value = value.into();
+        self.handles.insert(value.clone(), handle);
+        self.values.insert(handle, value);
+        handle
+    }
+
+    /// The handle of a value, if it has been interned.
+    #[inline]
+    pub fn get(&self, value: &T) -> Option<H64>
+    {
+        self.handles.get(value).copied()
+    }
+
+    /// The value with a handle, if there is one.
+    #[inline]
+    pub fn resolve(&self, handle: H64) -> Option<&T>
+    {
+        self.values.get(&handle).map(|value| &**value)
+    }
+
+    /// Iterate over the handles and values, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, &T)>
+    {
+        self.values.iter().map(|(&handle, value)| (handle, &**value))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + Hash + Eq> Default for Interner<T>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + Hash + Eq, Q> Extend<Q> for Interner<T>
+where
+    Q: Borrow<T> + Into<Arc<T>>,
+{
+    fn extend<I: IntoIterator<Item = Q>>(&mut self, iter: I)
+    {
+        for value in iter {
+            self.intern(value);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + Hash + Eq, Q> FromIterator<Q> for Interner<T>
+where
+    Q: Borrow<T> + Into<Arc<T>>,
+{
+    fn from_iter<I: IntoIterator<Item = Q>>(iter: I) -> Self
+    {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Interner<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(sorted_iter(&self.values)).finish()
+    }
+}
+
+/// Map from [`H64`]s to any number of values.
+///
+/// This is an [`H64HashMap`] of [`Vec`]s,
+/// without the bookkeeping of creating and removing empty groups.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct H64MultiMap<V>(H64HashMap<H64, Vec<V>>);
+
+#[cfg(feature = "alloc")]
+impl<V> H64MultiMap<V>
+{
+    /// Create an empty map.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self(H64HashMap::default())
+    }
+
+    /// The number of keys with at least one value.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.0.len()
+    }
+
+    /// Whether the map has no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.0.is_empty()
+    }
+
+    /// Add a value for a key, after any existing values.
+    #[inline]
+    pub fn insert(&mut self, key: H64, value: V)
+    {
+        self.0.entry(key).or_default().push(value);
+    }
+
+    /// The values for a key, in insertion order.
+    #[inline]
+    pub fn get(&self, key: H64) -> &[V]
+    {
+        self.0.get(&key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether a key has any values.
+    #[inline]
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.0.contains_key(&key)
+    }
+
+    /// Remove all values for a key.
+    #[inline]
+    pub fn remove(&mut self, key: H64) -> Vec<V>
+    {
+        self.0.remove(&key).unwrap_or_default()
+    }
+
+    /// Remove the first value for a key that equals `value`.
+    ///
+    /// Returns whether a value was removed.
+    pub fn remove_value(&mut self, key: H64, value: &V) -> bool
+    where
+        V: PartialEq,
+    {
+        let Some(values) = self.0.get_mut(&key) else { return false };
+        let Some(index) = values.iter().position(|v| v == value) else { return false };
+        values.remove(index);
+        if values.is_empty() {
+            self.0.remove(&key);
+        }
+        true
+    }
+
+    /// Iterate over the keys and their values, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, &[V])>
+    {
+        self.0.iter().map(|(&key, values)| (key, values.as_slice()))
+    }
+
+    /// The underlying map.
+    #[inline(always)]
+    pub fn as_inner(&self) -> &H64HashMap<H64, Vec<V>>
+    {
+        &self.0
+    }
+
+    /// The underlying map.
+    #[inline(always)]
+    pub fn into_inner(self) -> H64HashMap<H64, Vec<V>>
+    {
+        self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> Default for H64MultiMap<V>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> Extend<(H64, V)> for H64MultiMap<V>
+{
+    fn extend<I: IntoIterator<Item = (H64, V)>>(&mut self, iter: I)
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> FromIterator<(H64, V)> for H64MultiMap<V>
+{
+    fn from_iter<I: IntoIterator<Item = (H64, V)>>(iter: I) -> Self
+    {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: fmt::Debug> fmt::Debug for H64MultiMap<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+/// Counter of occurrences of [`H64`]s.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct H64Counter(H64HashMap<H64, u64>);
+
+#[cfg(feature = "alloc")]
+impl H64Counter
+{
+    /// Create an empty counter.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self(H64HashMap::default())
+    }
+
+    /// The number of distinct [`H64`]s counted.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.0.len()
+    }
+
+    /// Whether nothing has been counted.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.0.is_empty()
+    }
+
+    /// Count one occurrence of a key, returning its new count.
+    #[inline]
+    pub fn increment(&mut self, key: H64) -> u64
+    {
+        self.add(key, 1)
+    }
+
+    /// Count `n` occurrences of a key, returning its new count.
+    ///
+    /// The count saturates at [`u64::MAX`].
+    #[inline]
+    pub fn add(&mut self, key: H64, n: u64) -> u64
+    {
+        let count = self.0.entry(key).or_insert(0);
+        *count = count.saturating_add(n);
+        *count
+    }
+
+    /// The number of occurrences of a key.
+    #[inline]
+    pub fn count(&self, key: H64) -> u64
+    {
+        self.0.get(&key).copied().unwrap_or(0)
+    }
+
+    /// The total number of occurrences of all keys.
+    pub fn total(&self) -> u64
+    {
+        self.0.values().fold(0, |total, &count| total.saturating_add(count))
+    }
+
+    /// The `n` keys with the highest counts, highest first.
+    ///
+    /// Ties are broken by original [`u64`], so the result is deterministic.
+    pub fn most_common(&self, n: usize) -> Vec<(H64, u64)>
+    {
+        let mut counts: Vec<_> = self.0.iter().map(|(&key, &count)| (key, count)).collect();
+        counts.sort_unstable_by_key(|&(key, count)| (cmp::Reverse(count), key.unhash()));
+        counts.truncate(n);
+        counts
+    }
+
+    /// Add the counts of another counter to this one.
+    pub fn merge(&mut self, other: &Self)
+    {
+        for (&key, &count) in &other.0 {
+            self.add(key, count);
+        }
+    }
+
+    /// Iterate over the keys and their counts, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, u64)> + '_
+    {
+        self.0.iter().map(|(&key, &count)| (key, count))
+    }
+
+    /// The underlying map.
+    #[inline(always)]
+    pub fn as_inner(&self) -> &H64HashMap<H64, u64>
+    {
+        &self.0
+    }
+
+    /// The underlying map.
+    #[inline(always)]
+    pub fn into_inner(self) -> H64HashMap<H64, u64>
+    {
+        self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Extend<H64> for H64Counter
+{
+    fn extend<I: IntoIterator<Item = H64>>(&mut self, iter: I)
+    {
+        for key in iter {
+            self.increment(key);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<H64> for H64Counter
+{
+    fn from_iter<I: IntoIterator<Item = H64>>(iter: I) -> Self
+    {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64Counter
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+/// Bidirectional map between [`H64`]s and values.
+///
+/// Every key maps to exactly one value and vice versa.
+/// The [`H64`] side uses [`H64Hasher`];
+/// the value side keeps a clone of each value.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct H64BiMap<V>
+{
+    values: H64HashMap<H64, V>,
+    keys: HashMap<V, H64, RandomState>,
+}
+
+#[cfg(feature = "std")]
+impl<V: Hash + Eq + Clone> H64BiMap<V>
+{
+    /// Create an empty map.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self { values: H64HashMap::default(), keys: HashMap::default() }
+    }
+
+    /// The number of pairs in the map.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.values.len()
+    }
+
+    /// Whether the map has no pairs.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.values.is_empty()
+    }
+
+    /// Insert a pair.
+    ///
+    /// Any pairs with the same key or the same value are removed first,
+    /// so that both directions stay one-to-one.
+    pub fn insert(&mut self, key: H64, value: V)
+    {
+        self.remove_by_key(key);
+        self.remove_by_value(&value);
+        self.keys.insert(value.clone(), key);
+        self.values.insert(key, value);
+    }
+
+    /// The value for a key.
+    #[inline]
+    pub fn get_by_key(&self, key: H64) -> Option<&V>
+    {
+        self.values.get(&key)
+    }
+
+    /// The key for a value.
+    #[inline]
+    pub fn get_by_value(&self, value: &V) -> Option<H64>
+    {
+        self.keys.get(value).copied()
+    }
+
+    /// Whether there is a pair with a key.
+    #[inline]
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.values.contains_key(&key)
+    }
+
+    /// Whether there is a pair with a value.
+    #[inline]
+    pub fn contains_value(&self, value: &V) -> bool
+    {
+        self.keys.contains_key(value)
+    }
+
+    /// Remove the pair with a key, returning its value.
+    pub fn remove_by_key(&mut self, key: H64) -> Option<V>
+    {
+        let value = self.values.remove(&key)?;
+        self.keys.remove(&value);
+        Some(value)
+    }
+
+    /// Remove the pair with a value, returning its key.
+    pub fn remove_by_value(&mut self, value: &V) -> Option<H64>
+    {
+        let key = self.keys.remove(value)?;
+        self.values.remove(&key);
+        Some(key)
+    }
+
+    /// Iterate over the pairs, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, &V)>
+    {
+        self.values.iter().map(|(&key, value)| (key, value))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: Hash + Eq + Clone> Default for H64BiMap<V>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: Hash + Eq + Clone> Extend<(H64, V)> for H64BiMap<V>
+{
+    fn extend<I: IntoIterator<Item = (H64, V)>>(&mut self, iter: I)
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: Hash + Eq + Clone> FromIterator<(H64, V)> for H64BiMap<V>
+{
+    fn from_iter<I: IntoIterator<Item = (H64, V)>>(iter: I) -> Self
+    {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: fmt::Debug> fmt::Debug for H64BiMap<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.values.iter()).finish()
+    }
+}
+
+/// Cache with [`H64`] keys that evicts the least recently used entry.
+///
+/// Entries live in a [`Vec`], linked in order of use,
+/// and are found through an [`H64HashMap`],
+/// so [`get`] and [`put`] take constant time.
+/// An optional callback receives every evicted entry.
+///
+/// [`get`]: `Self::get`
+/// [`put`]: `Self::put`
+#[cfg(feature = "alloc")]
+pub struct H64LruCache<V>
+{
+    capacity: usize,
+    indices: H64HashMap<H64, usize>,
+    nodes: Vec<LruNode<V>>,
+    /// Most recently used node.
+    head: usize,
+    /// Least recently used node.
+    tail: usize,
+    on_evict: Option<Box<dyn FnMut(H64, V) + Send>>,
+}
+
+#[cfg(feature = "alloc")]
+struct LruNode<V>
+{
+    key: H64,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+/// Index of no [`LruNode`].
+#[cfg(feature = "alloc")]
+const NIL: usize = usize::MAX;
+
+#[cfg(feature = "alloc")]
+impl<V> H64LruCache<V>
+{
+    /// Create an empty cache that holds up to `capacity` entries.
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self
+    {
+        assert!(capacity > 0, "H64LruCache capacity must be nonzero");
+        Self {
+            capacity,
+            indices: H64HashMap::with_capacity_and_hasher(capacity, Default::default()),
+            nodes: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            on_evict: None,
+        }
+    }
+
+    /// Create an empty cache that passes evicted entries to a callback.
+    ///
+    /// The callback is not called for entries that are
+    /// [`remove`](Self::remove)d, replaced, or dropped with the cache.
+    pub fn with_on_evict(capacity: usize, on_evict: impl FnMut(H64, V) + Send + 'static) -> Self
+    {
+        Self { on_evict: Some(Box::new(on_evict)), ..Self::new(capacity) }
+    }
+
+    /// The maximum number of entries.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize
+    {
+        self.capacity
+    }
+
+    /// The number of entries.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.nodes.len()
+    }
+
+    /// Whether the cache has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.nodes.is_empty()
+    }
+
+    /// Whether the cache has an entry for a key, without marking it as used.
+    #[inline]
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.indices.contains_key(&key)
+    }
+
+    /// The value for a key, marking it as most recently used.
+    pub fn get(&mut self, key: H64) -> Option<&V>
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// The value for a key, marking it as most recently used.
+    pub fn get_mut(&mut self, key: H64) -> Option<&mut V>
+    {
+        let index = *self.indices.get(&key)?;
+        self.touch(index);
+        Some(&mut self.nodes[index].value)
+    }
+
+    /// The value for a key, without marking it as used.
+    #[inline]
+    pub fn peek(&self, key: H64) -> Option<&V>
+    {
+        self.indices.get(&key).map(|&index| &self.nodes[index].value)
+    }
+
+    /// Insert an entry, marking it as most recently used.
+    ///
+    /// Returns the previous value for the key, if there was one.
+    /// Otherwise, if the cache is full,
+    /// the least recently used entry is evicted.
+    pub fn put(&mut self, key: H64, value: V) -> Option<V>
+    {
+        if let Some(&index) = self.indices.get(&key) {
+            self.touch(index);
+            return Some(mem::replace(&mut self.nodes[index].value, value));
+        }
+        if self.nodes.len() == self.capacity {
+            let (key, value) = self.remove_at(self.tail);
+            if let Some(on_evict) = &mut self.on_evict {
+                on_evict(key, value);
+            }
+        }
+        let index = self.nodes.len();
+        self.nodes.push(LruNode { key, value, prev: NIL, next: NIL });
+        self.push_front(index);
+        self.indices.insert(key, index);
+        None
+    }
+
+    /// Remove the entry for a key, returning its value.
+    pub fn remove(&mut self, key: H64) -> Option<V>
+    {
+        let index = *self.indices.get(&key)?;
+        Some(self.remove_at(index).1)
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self)
+    {
+        self.indices.clear();
+        self.nodes.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    /// Iterate over the entries, most recently used first.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, &V)>
+    {
+        let mut index = self.head;
+        core::iter::from_fn(move || {
+            let node = self.nodes.get(index)?;
+            index = node.next;
+            Some((node.key, &node.value))
+        })
+    }
+
+    fn touch(&mut self, index: usize)
+    {
+        if self.head != index {
+            self.unlink(index);
+            self.push_front(index);
+        }
+    }
+
+    fn push_front(&mut self, index: usize)
+    {
+        self.nodes[index].prev = NIL;
+        self.nodes[index].next = self.head;
+        match self.nodes.get_mut(self.head) {
+            Some(head) => head.prev = index,
+            None => self.tail = index,
+        }
+        self.head = index;
+    }
+
+    fn unlink(&mut self, index: usize)
+    {
+        let LruNode { prev, next, .. } = self.nodes[index];
+        match self.nodes.get_mut(prev) {
+            Some(node) => node.next = next,
+            None => self.head = next,
+        }
+        match self.nodes.get_mut(next) {
+            Some(node) => node.prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    /// Remove a node, moving the last node into its place.
+    fn remove_at(&mut self, index: usize) -> (H64, V)
+    {
+        self.unlink(index);
+        let node = self.nodes.swap_remove(index);
+        self.indices.remove(&node.key);
+        if let Some(&LruNode { key, prev, next, .. }) = self.nodes.get(index) {
+            match self.nodes.get_mut(prev) {
+                Some(node) => node.next = index,
+                None => self.head = index,
+            }
+            match self.nodes.get_mut(next) {
+                Some(node) => node.prev = index,
+                None => self.tail = index,
+            }
+            self.indices.insert(key, index);
+        }
+        (node.key, node.value)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: fmt::Debug> fmt::Debug for H64LruCache<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Cache with [`H64`] keys whose entries expire after a fixed duration.
+///
+/// Expired entries are removed lazily when accessed,
+/// or all at once with [`purge_expired`](Self::purge_expired).
+/// Every method that reads the clock has an `_at` variant
+/// that takes the current time instead, for testing and batching.
+#[cfg(feature = "std")]
+pub struct H64TtlCache<V>
+{
+    ttl: Duration,
+    /// The expiry is [`None`] if it is too far in the future to represent.
+    entries: H64HashMap<H64, (V, Option<Instant>)>,
+}
+
+#[cfg(feature = "std")]
+impl<V> H64TtlCache<V>
+{
+    /// Create an empty cache whose entries expire `ttl` after insertion.
+    ///
+    /// A TTL of [`Duration::MAX`] means entries never expire.
+    #[inline]
+    pub fn new(ttl: Duration) -> Self
+    {
+        Self { ttl, entries: H64HashMap::default() }
+    }
+
+    /// The duration after which entries expire.
+    #[inline(always)]
+    pub fn ttl(&self) -> Duration
+    {
+        self.ttl
+    }
+
+    /// The number of entries, including expired entries not yet removed.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    /// Whether the cache has no entries, not even expired ones.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.entries.is_empty()
+    }
+
+    /// Insert an entry that expires after the cache's TTL.
+    ///
+    /// Returns the previous value for the key, if it had not expired.
+    #[inline]
+    pub fn insert(&mut self, key: H64, value: V) -> Option<V>
+    {
+        self.insert_at(key, value, Instant::now())
+    }
+
+    /// Like [`insert`](Self::insert), at the given time.
+    pub fn insert_at(&mut self, key: H64, value: V, now: Instant) -> Option<V>
+    {
+        let expires = now.checked_add(self.ttl);
+        let (value, expired) = self.entries.insert(key, (value, expires))?;
+        Self::is_live(expired, now).then_some(value)
+    }
+
+    /// The value for a key, unless it has expired.
+    #[inline]
+    pub fn get(&mut self, key: H64) -> Option<&V>
+    {
+        self.get_at(key, Instant::now())
+    }
+
+    /// Like [`get`](Self::get), at the given time.
+    pub fn get_at(&mut self, key: H64, now: Instant) -> Option<&V>
+    {
+        self.remove_expired(key, now);
+        self.entries.get(&key).map(|(value, _)| value)
+    }
+
+    /// Whether the cache has an unexpired entry for a key.
+    #[inline]
+    pub fn contains_key(&self, key: H64) -> bool
+    {
+        self.contains_key_at(key, Instant::now())
+    }
+
+    /// Like [`contains_key`](Self::contains_key), at the given time.
+    #[inline]
+    pub fn contains_key_at(&self, key: H64, now: Instant) -> bool
+    {
+        self.entries.get(&key).is_some_and(|&(_, expires)| Self::is_live(expires, now))
+    }
+
+    /// Remove the entry for a key, returning its value unless it had expired.
+    #[inline]
+    pub fn remove(&mut self, key: H64) -> Option<V>
+    {
+        self.remove_at(key, Instant::now())
+    }
+
+    /// Like [`remove`](Self::remove), at the given time.
+    pub fn remove_at(&mut self, key: H64, now: Instant) -> Option<V>
+    {
+        let (value, expires) = self.entries.remove(&key)?;
+        Self::is_live(expires, now).then_some(value)
+    }
+
+    /// Remove all expired entries, returning how many were removed.
+    #[inline]
+    pub fn purge_expired(&mut self) -> usize
+    {
+        self.purge_expired_at(Instant::now())
+    }
+
+    /// Like [`purge_expired`](Self::purge_expired), at the given time.
+    pub fn purge_expired_at(&mut self, now: Instant) -> usize
+    {
+        let len = self.entries.len();
+        self.entries.retain(|_, &mut (_, expires)| Self::is_live(expires, now));
+        len - self.entries.len()
+    }
+
+    fn remove_expired(&mut self, key: H64, now: Instant)
+    {
+        if !self.contains_key_at(key, now) {
+            self.entries.remove(&key);
+        }
+    }
+
+    #[inline(always)]
+    fn is_live(expires: Option<Instant>, now: Instant) -> bool
+    {
+        expires.is_none_or(|expires| expires > now)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: fmt::Debug> fmt::Debug for H64TtlCache<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.entries.iter().map(|(key, (value, _))| (key, value))).finish()
+    }
+}
+
+/// Storage of values with [`H64`] handles.
+///
+/// Inserting a value allocates a fresh handle from an [`H64Allocator`],
+/// so handles are never reused and stay valid until removed.
+/// Values are stored densely;
+/// removing one moves the last value into its place.
+#[cfg(feature = "alloc")]
+pub struct H64Registry<T>
+{
+    allocator: H64Allocator,
+    indices: H64HashMap<H64, usize>,
+    handles: Vec<H64>,
+    values: Vec<T>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> H64Registry<T>
+{
+    /// Create an empty registry.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self {
+            allocator: H64Allocator::new(),
+            indices: H64HashMap::default(),
+            handles: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// The number of values.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.values.len()
+    }
+
+    /// Whether the registry has no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.values.is_empty()
+    }
+
+    /// Insert a value, returning its handle.
+    ///
+    /// Panics if the allocator is exhausted.
+    pub fn insert(&mut self, value: T) -> H64
+    {
+        let handle = self.allocator.alloc();
+        self.indices.insert(handle, self.values.len());
+        self.handles.push(handle);
+        self.values.push(value);
+        handle
+    }
+
+    /// Whether a handle refers to a value.
+    #[inline]
+    pub fn contains(&self, handle: H64) -> bool
+    {
+        self.indices.contains_key(&handle)
+    }
+
+    /// The value for a handle.
+    #[inline]
+    pub fn get(&self, handle: H64) -> Option<&T>
+    {
+        self.indices.get(&handle).map(|&index| &self.values[index])
+    }
+
+    /// The value for a handle.
+    #[inline]
+    pub fn get_mut(&mut self, handle: H64) -> Option<&mut T>
+    {
+        self.indices.get(&handle).map(|&index| &mut self.values[index])
+    }
+
+    /// Remove the value for a handle.
+    pub fn remove(&mut self, handle: H64) -> Option<T>
+    {
+        let index = self.indices.remove(&handle)?;
+        self.handles.swap_remove(index);
+        if let Some(&moved) = self.handles.get(index) {
+            self.indices.insert(moved, index);
+        }
+        Some(self.values.swap_remove(index))
+    }
+
+    /// The values, in no particular order.
+    #[inline(always)]
+    pub fn values(&self) -> &[T]
+    {
+        &self.values
+    }
+
+    /// The values, in no particular order.
+    #[inline(always)]
+    pub fn values_mut(&mut self) -> &mut [T]
+    {
+        &mut self.values
+    }
+
+    /// Iterate over the handles and values, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, &T)>
+    {
+        Iterator::zip(self.handles.iter().copied(), &self.values)
+    }
+
+    /// Iterate over the handles and values, in no particular order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (H64, &mut T)>
+    {
+        Iterator::zip(self.handles.iter().copied(), &mut self.values)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Default for H64Registry<T>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: fmt::Debug> fmt::Debug for H64Registry<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Storage of values with generation-checked handles.
+///
+/// Like [`H64Registry`], but handles are [`GenerationalH64`]s
+/// from a [`GenerationalAllocator`].
+/// Removing a value frees its index for reuse with the next generation,
+/// so a stale handle never refers to a newer value.
+/// Values are stored by index,
+/// in a [`Vec`] as long as the most indices ever in use at once.
+#[cfg(feature = "alloc")]
+pub struct H64VersionedRegistry<T>
+{
+    allocator: GenerationalAllocator,
+    values: Vec<Option<T>>,
+    len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> H64VersionedRegistry<T>
+{
+    /// Create an empty registry.
+    #[inline]
+    pub const fn new() -> Self
+    {
+        Self { allocator: GenerationalAllocator::new(), values: Vec::new(), len: 0 }
+    }
+
+    /// The number of values.
+    #[inline(always)]
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    /// Whether the registry has no values.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    /// Insert a value, returning its handle.
+    ///
+    /// Panics if all indices are in use.
+    pub fn insert(&mut self, value: T) -> GenerationalH64
+    {
+        let handle = self.allocator.alloc();
+        let index = handle.index() as usize;
+        if index == self.values.len() {
+            self.values.push(None);
+        }
+        self.values[index] = Some(value);
+        self.len += 1;
+        handle
+    }
+
+    /// Whether a handle refers to a value.
+    #[inline]
+    pub fn contains(&self, handle: GenerationalH64) -> bool
+    {
+        self.allocator.is_allocated(handle)
+    }
+
+    /// The value for a handle, unless it is stale.
+    #[inline]
+    pub fn get(&self, handle: GenerationalH64) -> Option<&T>
+    {
+        if !self.contains(handle) {
+            return None;
+        }
+        self.values[handle.index() as usize].as_ref()
+    }
+
+    /// The value for a handle, unless it is stale.
+    #[inline]
+    pub fn get_mut(&mut self, handle: GenerationalH64) -> Option<&mut T>
+    {
+        if !self.contains(handle) {
+            return None;
+        }
+        self.values[handle.index() as usize].as_mut()
+    }
+
+    /// Remove the value for a handle, unless it is stale.
+    pub fn remove(&mut self, handle: GenerationalH64) -> Option<T>
+    {
+        if !self.allocator.free(handle) {
+            return None;
+        }
+        self.len -= 1;
+        self.values[handle.index() as usize].take()
+    }
+
+    /// Iterate over the handles and values, in order of index.
+    pub fn iter(&self) -> impl Iterator<Item = (GenerationalH64, &T)>
+    {
+        Iterator::zip(self.allocator.slots.iter(), &self.values)
+            .enumerate()
+            .filter_map(|(index, (&(generation, _), value))| {
+                Some((GenerationalH64::new(index as u32, generation), value.as_ref()?))
+            })
+    }
+
+    /// Iterate over the handles and values, in order of index.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (GenerationalH64, &mut T)>
+    {
+        Iterator::zip(self.allocator.slots.iter(), &mut self.values)
+            .enumerate()
+            .filter_map(|(index, (&(generation, _), value))| {
+                Some((GenerationalH64::new(index as u32, generation), value.as_mut()?))
+            })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Default for H64VersionedRegistry<T>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: fmt::Debug> fmt::Debug for H64VersionedRegistry<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Arena of values with [`H64`] handles, freed all at once.
+///
+/// Values are stored contiguously in insertion order.
+/// The handle of the `i`th value represents `base + i`,
+/// so arenas with disjoint bases hand out distinct handles.
+/// Handles are invalidated by [`clear`](Self::clear),
+/// after which they refer to newly allocated values.
+#[cfg(feature = "alloc")]
+pub struct H64Arena<T>
+{
+    base: u64,
+    values: Vec<T>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> H64Arena<T>
+{
+    /// Create an empty arena whose handles start at zero.
+    #[inline]
+    pub const fn new() -> Self
+    {
+        Self::with_base(0)
+    }
+
+    /// Create an empty arena whose first handle represents `base`.
+    #[inline]
+    pub const fn with_base(base: u64) -> Self
+    {
+        Self { base, values: Vec::new() }
+    }
+
+    /// The original [`u64`] of the first handle.
+    #[inline(always)]
+    pub fn base(&self) -> u64
+    {
+        self.base
+    }
+
+    /// The number of values.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.values.len()
+    }
+
+    /// Whether the arena has no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.values.is_empty()
+    }
+
+    /// Store a value, returning its handle.
+    ///
+    /// Panics if the handle would exceed [`u64::MAX`].
+    pub fn alloc(&mut self, value: T) -> H64
+    {
+        let handle = self.handle(self.values.len()).expect("H64Arena exhausted");
+        self.values.push(value);
+        handle
+    }
+
+    /// The value for a handle.
+    #[inline]
+    pub fn get(&self, handle: H64) -> Option<&T>
+    {
+        self.values.get(self.index(handle)?)
+    }
+
+    /// The value for a handle.
+    #[inline]
+    pub fn get_mut(&mut self, handle: H64) -> Option<&mut T>
+    {
+        let index = self.index(handle)?;
+        self.values.get_mut(index)
+    }
+
+    /// Drop all values, invalidating all handles.
+    #[inline]
+    pub fn clear(&mut self)
+    {
+        self.values.clear();
+    }
+
+    /// The values, in insertion order.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[T]
+    {
+        &self.values
+    }
+
+    /// Iterate over the handles and values, in insertion order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (H64, &T)> + ExactSizeIterator
+    {
+        let base = self.base;
+        self.values.iter().enumerate()
+            .map(move |(index, value)| (H64::hash(base + index as u64), value))
+    }
+
+    /// Iterate over the handles and values, in insertion order.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = (H64, &mut T)> + ExactSizeIterator
+    {
+        let base = self.base;
+        self.values.iter_mut().enumerate()
+            .map(move |(index, value)| (H64::hash(base + index as u64), value))
+    }
+
+    #[inline]
+    fn handle(&self, index: usize) -> Option<H64>
+    {
+        self.base.checked_add(index as u64).map(H64::hash)
+    }
+
+    #[inline]
+    fn index(&self, handle: H64) -> Option<usize>
+    {
+        usize::try_from(handle.unhash().checked_sub(self.base)?).ok()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Default for H64Arena<T>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+/// Panics if the handle does not refer to a value.
+#[cfg(feature = "alloc")]
+impl<T> Index<H64> for H64Arena<T>
+{
+    type Output = T;
+
+    #[inline]
+    fn index(&self, handle: H64) -> &T
+    {
+        self.get(handle).expect("invalid H64Arena handle")
+    }
+}
+
+/// Panics if the handle does not refer to a value.
+#[cfg(feature = "alloc")]
+impl<T> IndexMut<H64> for H64Arena<T>
+{
+    #[inline]
+    fn index_mut(&mut self, handle: H64) -> &mut T
+    {
+        self.get_mut(handle).expect("invalid H64Arena handle")
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: fmt::Debug> fmt::Debug for H64Arena<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Directed graph with [`H64`] nodes.
+///
+/// Each node has a list of outgoing edges, in the order they were added.
+/// Parallel edges and self-loops are allowed.
+/// Traversals visit nodes and edges in insertion order,
+/// so their results are deterministic.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default)]
+pub struct H64Graph
+{
+    nodes: Vec<H64>,
+    edges: H64HashMap<H64, Vec<H64>>,
+    edge_count: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl H64Graph
+{
+    /// Create an empty graph.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// The number of nodes.
+    #[inline]
+    pub fn node_count(&self) -> usize
+    {
+        self.nodes.len()
+    }
+
+    /// The number of edges.
+    #[inline(always)]
+    pub fn edge_count(&self) -> usize
+    {
+        self.edge_count
+    }
+
+    /// Add a node, returning whether it is new.
+    pub fn add_node(&mut self, node: H64) -> bool
+    {
+        if self.edges.contains_key(&node) {
+            return false;
+        }
+        self.edges.insert(node, Vec::new());
+        self.nodes.push(node);
+        true
+    }
+
+    /// Add an edge, adding its nodes if they are new.
+    pub fn add_edge(&mut self, from: H64, to: H64)
+    {
+        self.add_node(from);
+        self.add_node(to);
+        self.edges.get_mut(&from).unwrap().push(to);
+        self.edge_count += 1;
+    }
+
+    /// Whether the graph has a node.
+    #[inline]
+    pub fn contains_node(&self, node: H64) -> bool
+    {
+        self.edges.contains_key(&node)
+    }
+
+    /// Whether the graph has an edge.
+    #[inline]
+    pub fn contains_edge(&self, from: H64, to: H64) -> bool
+    {
+        self.neighbors(from).contains(&to)
+    }
+
+    /// The nodes, in insertion order.
+    #[inline(always)]
+    pub fn nodes(&self) -> &[H64]
+    {
+        &self.nodes
+    }
+
+    /// The targets of the outgoing edges of a node, in insertion order.
+    ///
+    /// This is empty if the graph does not have the node.
+    #[inline]
+    pub fn neighbors(&self, node: H64) -> &[H64]
+    {
+        self.edges.get(&node).map_or(&[], Vec::as_slice)
+    }
+
+    /// The nodes reachable from `start`, in breadth-first order.
+    pub fn bfs(&self, start: H64) -> Vec<H64>
+    {
+        if !self.contains_node(start) {
+            return Vec::new();
+        }
+        let mut visited = H64HashSet::from_iter([start]);
+        let mut queue = VecDeque::from([start]);
+        let mut order = Vec::new();
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for &next in self.neighbors(node) {
+                if visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        order
+    }
+
+    /// The nodes reachable from `start`, in depth-first preorder.
+    pub fn dfs(&self, start: H64) -> Vec<H64>
+    {
+        if !self.contains_node(start) {
+            return Vec::new();
+        }
+        let mut visited = H64HashSet::default();
+        let mut stack = vec![start];
+        let mut order = Vec::new();
+        while let Some(node) = stack.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            order.push(node);
+            stack.extend(self.neighbors(node).iter().rev().filter(|next| !visited.contains(next)));
+        }
+        order
+    }
+
+    /// Order the nodes so that every edge points forward.
+    ///
+    /// Returns [`None`] if the graph has a cycle.
+    /// Among valid orders, nodes are taken in insertion order when possible.
+    pub fn topological_sort(&self) -> Option<Vec<H64>>
+    {
+        let mut in_degrees: H64HashMap<H64, usize> =
+            self.nodes.iter().map(|&node| (node, 0)).collect();
+        for targets in self.edges.values() {
+            for target in targets {
+                *in_degrees.get_mut(target).unwrap() += 1;
+            }
+        }
+        let mut queue: VecDeque<H64> =
+            self.nodes.iter().copied().filter(|node| in_degrees[node] == 0).collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for next in self.neighbors(node) {
+                let in_degree = in_degrees.get_mut(next).unwrap();
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    queue.push_back(*next);
+                }
+            }
+        }
+        (order.len() == self.nodes.len()).then_some(order)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64Graph
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let entries = self.nodes.iter().map(|&node| (node, self.neighbors(node)));
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+/// Disjoint sets of [`H64`]s.
+///
+/// Every [`H64`] starts out in a set of its own,
+/// without taking up space until it is [`union`](Self::union)ed.
+/// Sets are merged by rank and paths are compressed,
+/// so operations take nearly constant amortized time.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default)]
+pub struct H64UnionFind
+{
+    /// Parent and rank of each element that is not in a set of its own.
+    nodes: H64HashMap<H64, (H64, u32)>,
+    set_count: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl H64UnionFind
+{
+    /// Create a structure in which every [`H64`] is in a set of its own.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// The number of sets with more than one element.
+    #[inline(always)]
+    pub fn set_count(&self) -> usize
+    {
+        self.set_count
+    }
+
+    /// The representative of the set containing an element.
+    ///
+    /// Two elements are in the same set if they have the same representative.
+    pub fn find(&mut self, element: H64) -> H64
+    {
+        let mut root = element;
+        while let Some(&(parent, _)) = self.nodes.get(&root) {
+            if parent == root {
+                break;
+            }
+            root = parent;
+        }
+        let mut node = element;
+        while node != root {
+            let (parent, _) = self.nodes.get_mut(&node).unwrap();
+            node = mem::replace(parent, root);
+        }
+        root
+    }
+
+    /// Merge the sets containing two elements.
+    ///
+    /// Returns whether they were in different sets.
+    pub fn union(&mut self, a: H64, b: H64) -> bool
+    {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let rank_a = self.nodes.get(&a).map_or(0, |&(_, rank)| rank);
+        let rank_b = self.nodes.get(&b).map_or(0, |&(_, rank)| rank);
+        // Roots have a nonzero rank, unless they are in a set of their own.
+        let singletons = usize::from(rank_a == 0) + usize::from(rank_b == 0);
+        self.set_count = self.set_count + singletons - 1;
+        let (root, child, rank) = match rank_a.cmp(&rank_b) {
+            cmp::Ordering::Less => (b, a, rank_b),
+            cmp::Ordering::Greater => (a, b, rank_a),
+            cmp::Ordering::Equal => (a, b, rank_a + 1),
+        };
+        self.nodes.insert(root, (root, rank));
+        self.nodes.entry(child).or_insert((child, 0)).0 = root;
+        true
+    }
+
+    /// Whether two elements are in the same set.
+    #[inline]
+    pub fn same_set(&mut self, a: H64, b: H64) -> bool
+    {
+        self.find(a) == self.find(b)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64UnionFind
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let parents = self.nodes.iter().map(|(node, (parent, _))| (node, parent));
+        f.debug_map().entries(parents).finish()
+    }
+}
+
+/// Map from ranges of original [`u64`]s to values.
+///
+/// Ranges never overlap: inserting a range overwrites the overlapping parts
+/// of existing ranges, splitting them if needed.
+/// This suits blocks of [`H64`]s from [`H64Allocator::alloc_range`],
+/// such as recording which shard owns which IDs.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct H64IntervalMap<V>(BTreeMap<u64, (u64, V)>);
+
+#[cfg(feature = "alloc")]
+impl<V> H64IntervalMap<V>
+{
+    /// Create an empty map.
+    #[inline]
+    pub const fn new() -> Self
+    {
+        Self(BTreeMap::new())
+    }
+
+    /// The number of ranges.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.0.len()
+    }
+
+    /// Whether the map has no ranges.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.0.is_empty()
+    }
+
+    /// Map a range to a value, overwriting any overlapping ranges.
+    ///
+    /// Empty ranges are ignored.
+    pub fn insert(&mut self, range: Range<u64>, value: V)
+    where
+        V: Clone,
+    {
+        if range.is_empty() {
+            return;
+        }
+        self.remove(range.clone());
+        self.0.insert(range.start, (range.end, value));
+    }
+
+    /// Unmap a range, splitting any ranges that partially overlap it.
+    pub fn remove(&mut self, range: Range<u64>)
+    where
+        V: Clone,
+    {
+        let starts: Vec<u64> = self.overlapping(range.clone()).map(|(r, _)| r.start).collect();
+        for start in starts {
+            let (end, value) = self.0.remove(&start).unwrap();
+            if end > range.end {
+                self.0.insert(range.end, (end, value.clone()));
+            }
+            if start < range.start {
+                self.0.insert(start, (range.start, value));
+            }
+        }
+    }
+
+    /// The value for the range containing the original [`u64`] of a [`H64`].
+    #[inline]
+    pub fn get(&self, h64: H64) -> Option<&V>
+    {
+        self.get_entry(h64.unhash()).map(|(_, value)| value)
+    }
+
+    /// The range containing an original [`u64`], and its value.
+    pub fn get_entry(&self, i: u64) -> Option<(Range<u64>, &V)>
+    {
+        let (&start, (end, value)) = self.0.range(..= i).next_back()?;
+        (i < *end).then_some((start .. *end, value))
+    }
+
+    /// Iterate over the ranges that overlap a range, in order.
+    pub fn overlapping(&self, range: Range<u64>) -> impl Iterator<Item = (Range<u64>, &V)>
+    {
+        let end = range.end.max(range.start);
+        let first = self.0.range(.. range.start).next_back()
+            .filter(|(_, (first_end, _))| *first_end > range.start && range.start < end);
+        Iterator::chain(first.into_iter(), self.0.range(range.start .. end))
+            .map(|(&start, (end, value))| (start .. *end, value))
+    }
+
+    /// Iterate over the ranges, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (Range<u64>, &V)>
+    {
+        self.0.iter().map(|(&start, (end, value))| (start .. *end, value))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: fmt::Debug> fmt::Debug for H64IntervalMap<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Priority queue of [`H64`]s whose priorities can be changed.
+///
+/// Like [`BinaryHeap`](alloc::collections::BinaryHeap), this pops the highest priority first;
+/// wrap priorities in [`Reverse`](cmp::Reverse) to pop the lowest first.
+/// An [`H64HashMap`] tracks the position of each key in the heap,
+/// so [`change_priority`](Self::change_priority) and [`remove`](Self::remove)
+/// take logarithmic time.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct H64PriorityQueue<P>
+{
+    heap: Vec<(H64, P)>,
+    positions: H64HashMap<H64, usize>,
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Ord> H64PriorityQueue<P>
+{
+    /// Create an empty queue.
+    #[inline]
+    pub fn new() -> Self
+    {
+        Self { heap: Vec::new(), positions: H64HashMap::default() }
+    }
+
+    /// The number of keys in the queue.
+    #[inline]
+    pub fn len(&self) -> usize
+    {
+        self.heap.len()
+    }
+
+    /// Whether the queue is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool
+    {
+        self.heap.is_empty()
+    }
+
+    /// Whether a key is in the queue.
+    #[inline]
+    pub fn contains(&self, key: H64) -> bool
+    {
+        self.positions.contains_key(&key)
+    }
+
+    /// The priority of a key.
+    #[inline]
+    pub fn priority(&self, key: H64) -> Option<&P>
+    {
+        self.positions.get(&key).map(|&i| &self.heap[i].1)
+    }
+
+    /// The key with the highest priority.
+    #[inline]
+    pub fn peek(&self) -> Option<(H64, &P)>
+    {
+        self.heap.first().map(|(key, priority)| (*key, priority))
+    }
+
+    /// Add a key, or change its priority if it is already in the queue.
+    ///
+    /// Returns the previous priority of the key, if any.
+    pub fn push(&mut self, key: H64, priority: P) -> Option<P>
+    {
+        if self.contains(key) {
+            return self.change_priority(key, priority);
+        }
+        let i = self.heap.len();
+        self.heap.push((key, priority));
+        self.positions.insert(key, i);
+        self.sift_up(i);
+        None
+    }
+
+    /// Remove the key with the highest priority.
+    pub fn pop(&mut self) -> Option<(H64, P)>
+    {
+        let key = self.heap.first()?.0;
+        self.remove(key).map(|priority| (key, priority))
+    }
+
+    /// Change the priority of a key, returning the previous priority.
+    ///
+    /// Does nothing and returns [`None`] if the key is not in the queue.
+    pub fn change_priority(&mut self, key: H64, priority: P) -> Option<P>
+    {
+        let i = *self.positions.get(&key)?;
+        let old = mem::replace(&mut self.heap[i].1, priority);
+        if self.heap[i].1 > old {
+            self.sift_up(i);
+        } else {
+            self.sift_down(i);
+        }
+        Some(old)
+    }
+
+    /// Remove a key, returning its priority.
+    pub fn remove(&mut self, key: H64) -> Option<P>
+    {
+        let i = self.positions.remove(&key)?;
+        let (_, priority) = self.heap.swap_remove(i);
+        if i < self.heap.len() {
+            self.positions.insert(self.heap[i].0, i);
+            self.sift_down(i);
+            self.sift_up(i);
+        }
+        Some(priority)
+    }
+
+    /// Iterate over the keys and priorities, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (H64, &P)>
+    {
+        self.heap.iter().map(|(key, priority)| (*key, priority))
+    }
+
+    fn sift_up(&mut self, mut i: usize)
+    {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if self.heap[i].1 <= self.heap[parent].1 {
+                break;
+            }
+            self.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize)
+    {
+        loop {
+            let mut largest = i;
+            for child in [2 * i + 1, 2 * i + 2] {
+                if child < self.heap.len() && self.heap[child].1 > self.heap[largest].1 {
+                    largest = child;
+                }
+            }
+            if largest == i {
+                break;
+            }
+            self.swap(i, largest);
+            i = largest;
+        }
+    }
+
+    fn swap(&mut self, i: usize, j: usize)
+    {
+        self.heap.swap(i, j);
+        self.positions.insert(self.heap[i].0, i);
+        self.positions.insert(self.heap[j].0, j);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Ord> Default for H64PriorityQueue<P>
+{
+    #[inline]
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Ord> Extend<(H64, P)> for H64PriorityQueue<P>
+{
+    fn extend<I: IntoIterator<Item = (H64, P)>>(&mut self, iter: I)
+    {
+        for (key, priority) in iter {
+            self.push(key, priority);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Ord> FromIterator<(H64, P)> for H64PriorityQueue<P>
+{
+    fn from_iter<I: IntoIterator<Item = (H64, P)>>(iter: I) -> Self
+    {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: fmt::Debug> fmt::Debug for H64PriorityQueue<P>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let entries = self.heap.iter().map(|(key, priority)| (key, priority));
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+/// Bloom filter of [`H64`]s.
+///
+/// Membership tests may return false positives, but never false negatives.
+/// The probe positions of a [`H64`] are derived from its hashed value
+/// by double hashing, so no further hashing is needed.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Eq, PartialEq)]
+pub struct H64BloomFilter
+{
+    words: Box<[u64]>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+#[cfg(feature = "alloc")]
+impl H64BloomFilter
+{
+    /// Create an empty filter with `num_bits` bits and `num_hashes` probes per [`H64`].
+    ///
+    /// Panics if either is zero.
+    pub fn new(num_bits: u64, num_hashes: u32) -> Self
+    {
+        assert!(num_bits > 0 && num_hashes > 0, "H64BloomFilter must have bits and hashes");
+        let words = usize::try_from(num_bits.div_ceil(64)).expect("H64BloomFilter too large");
+        Self { words: vec![0; words].into_boxed_slice(), num_bits, num_hashes }
+    }
+
+    /// Create an empty filter sized for `expected_items` [`H64`]s
+    /// with the given false positive rate.
+    ///
+    /// Panics unless the rate is strictly between zero and one.
+    #[cfg(feature = "std")]
+    pub fn with_rate(expected_items: usize, false_positive_rate: f64) -> Self
+    {
+        assert!(
+            0.0 < false_positive_rate && false_positive_rate < 1.0,
+            "H64BloomFilter false positive rate must be between 0 and 1",
+        );
+        let n = expected_items.max(1) as f64;
+        let ln2 = core::f64::consts::LN_2;
+        let num_bits = (-n * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+        let num_hashes = (num_bits / n * ln2).round().max(1.0);
+        Self::new(num_bits as u64, num_hashes as u32)
+    }
+
+    /// Recreate a filter from its parameters and [`words`](Self::words).
+    ///
+    /// Returns [`None`] if the number of words does not match `num_bits`,
+    /// or if either parameter is zero.
+    pub fn from_words(num_bits: u64, num_hashes: u32, words: Vec<u64>) -> Option<Self>
+    {
+        if num_bits == 0 || num_hashes == 0 || words.len() as u64 != num_bits.div_ceil(64) {
+            return None;
+        }
+        Some(Self { words: words.into_boxed_slice(), num_bits, num_hashes })
+    }
+
+    /// The number of bits.
+    #[inline(always)]
+    pub fn num_bits(&self) -> u64
+    {
+        self.num_bits
+    }
+
+    /// The number of probes per [`H64`].
+    #[inline(always)]
+    pub fn num_hashes(&self) -> u32
+    {
+        self.num_hashes
+    }
+
+    /// The bit array, 64 bits per word.
+    #[inline(always)]
+    pub fn words(&self) -> &[u64]
+    {
+        &self.words
+    }
+
+    /// Insert a [`H64`], returning whether it was definitely not yet in the filter.
+    pub fn insert(&mut self, h64: H64) -> bool
+    {
+        let mut inserted = false;
+        for bit in self.probes(h64) {
+            let word = &mut self.words[(bit / 64) as usize];
+            inserted |= *word & 1 << (bit % 64) == 0;
+            *word |= 1 << (bit % 64);
+        }
+        inserted
+    }
+
+    /// Whether a [`H64`] may be in the filter.
+    pub fn contains(&self, h64: H64) -> bool
+    {
+        self.probes(h64).all(|bit| self.words[(bit / 64) as usize] & 1 << (bit % 64) != 0)
+    }
+
+    /// Add the [`H64`]s of another filter to this one.
+    ///
+    /// Panics if the filters have different parameters.
+    pub fn union_with(&mut self, other: &Self)
+    {
+        assert!(
+            self.num_bits == other.num_bits && self.num_hashes == other.num_hashes,
+            "H64BloomFilter parameters differ",
+        );
+        for (word, other) in Iterator::zip(self.words.iter_mut(), other.words.iter()) {
+            *word |= other;
+        }
+    }
+
+    /// Remove all [`H64`]s.
+    #[inline]
+    pub fn clear(&mut self)
+    {
+        self.words.fill(0);
+    }
+
+    fn probes(&self, h64: H64) -> impl Iterator<Item = u64>
+    {
+        let (h1, h2) = (h64.0, h64.0.rotate_left(32) | 1);
+        let num_bits = self.num_bits;
+        (0 .. u64::from(self.num_hashes)).map(move |i| {
+            let h = u64::wrapping_add(h1, u64::wrapping_mul(i, h2));
+            ((u128::from(h) * u128::from(num_bits)) >> 64) as u64
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64BloomFilter
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("H64BloomFilter")
+            .field("num_bits", &self.num_bits)
+            .field("num_hashes", &self.num_hashes)
+            .field("ones", &self.words.iter().map(|word| word.count_ones() as u64).sum::<u64>())
+            .finish()
+    }
+}
+
+/// Cuckoo filter of [`H64`]s, which supports removal.
+///
+/// Like [`H64BloomFilter`], membership tests may return false positives.
+/// Each [`H64`] is stored as a 16-bit fingerprint
+/// in one of two buckets of four slots,
+/// all derived from its hashed value.
+/// The false positive rate is about 8 in 65536, or 0.012%.
+/// Inserting tends to fail once the filter is about 95% full.
+/// Removing a [`H64`] that was never inserted may remove another one.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct H64CuckooFilter
+{
+    buckets: Box<[[u16; 4]]>,
+    len: usize,
+    /// Fingerprint that was evicted when the filter became full.
+    victim: Option<(usize, u16)>,
+    random: u64,
+}
+
+#[cfg(feature = "alloc")]
+impl H64CuckooFilter
+{
+    const MAX_KICKS: usize = 500;
+
+    /// Create an empty filter with room for at least `capacity` [`H64`]s.
+    ///
+    /// The number of buckets is rounded up to a power of two.
+    pub fn with_capacity(capacity: usize) -> Self
+    {
+        let buckets = capacity.div_ceil(4).max(1).next_power_of_two();
+        Self { buckets: vec![[0; 4]; buckets].into_boxed_slice(), len: 0, victim: None, random: 0 }
+    }
+
+    /// The number of slots.
+    #[inline]
+    pub fn capacity(&self) -> usize
+    {
+        4 * self.buckets.len()
+    }
+
+    /// The number of [`H64`]s in the filter.
+    #[inline(always)]
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    /// Whether the filter is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    /// Insert a [`H64`].
+    ///
+    /// Inserting a [`H64`] twice stores it twice,
+    /// so that it must also be removed twice.
+    /// Returns an error if the filter is full.
+    pub fn insert(&mut self, h64: H64) -> Result<(), CapacityError<H64>>
+    {
+        if self.victim.is_some() {
+            return Err(CapacityError(h64));
+        }
+        let (i, fingerprint) = self.locate(h64);
+        self.place(i, fingerprint);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Whether a [`H64`] may be in the filter.
+    pub fn contains(&self, h64: H64) -> bool
+    {
+        let (i1, fingerprint) = self.locate(h64);
+        let i2 = self.alternate(i1, fingerprint);
+        self.buckets[i1].contains(&fingerprint)
+            || self.buckets[i2].contains(&fingerprint)
+            || self.victim.is_some_and(|(i, f)| f == fingerprint && (i == i1 || i == i2))
+    }
+
+    /// Remove a [`H64`], returning whether it may have been in the filter.
+    pub fn remove(&mut self, h64: H64) -> bool
+    {
+        let (i1, fingerprint) = self.locate(h64);
+        let i2 = self.alternate(i1, fingerprint);
+        if self.victim.is_some_and(|(i, f)| f == fingerprint && (i == i1 || i == i2)) {
+            self.victim = None;
+            self.len -= 1;
+            return true;
+        }
+        for i in [i1, i2] {
+            if let Some(slot) = self.buckets[i].iter().position(|&f| f == fingerprint) {
+                self.buckets[i][slot] = 0;
+                self.len -= 1;
+                if let Some((i, fingerprint)) = self.victim.take() {
+                    self.place(i, fingerprint);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Remove all [`H64`]s.
+    pub fn clear(&mut self)
+    {
+        self.buckets.fill([0; 4]);
+        self.len = 0;
+        self.victim = None;
+    }
+
+    /// Compute the primary bucket and the fingerprint of a [`H64`].
+    #[inline]
+    fn locate(&self, h64: H64) -> (usize, u16)
+    {
+        let fingerprint = ((h64.0 >> 48) as u16).max(1);
+        (h64.0 as usize & (self.buckets.len() - 1), fingerprint)
+    }
+
+    /// Compute the other bucket of a fingerprint.
+    #[inline]
+    fn alternate(&self, i: usize, fingerprint: u16) -> usize
+    {
+        (i ^ H64::hash(u64::from(fingerprint)).0 as usize) & (self.buckets.len() - 1)
+    }
+
+    /// Store a fingerprint in an empty slot of a bucket, if there is one.
+    #[inline]
+    fn put(&mut self, i: usize, fingerprint: u16) -> bool
+    {
+        match self.buckets[i].iter_mut().find(|f| **f == 0) {
+            Some(slot) => { *slot = fingerprint; true },
+            None => false,
+        }
+    }
+
+    /// Store a fingerprint in one of its buckets, evicting others as needed.
+    fn place(&mut self, i: usize, mut fingerprint: u16)
+    {
+        let alternate = self.alternate(i, fingerprint);
+        if self.put(i, fingerprint) || self.put(alternate, fingerprint) {
+            return;
+        }
+        let mut i = if self.next_random() & 1 == 0 { i } else { alternate };
+        for _ in 0 .. Self::MAX_KICKS {
+            let slot = (self.next_random() % 4) as usize;
+            fingerprint = mem::replace(&mut self.buckets[i][slot], fingerprint);
+            i = self.alternate(i, fingerprint);
+            if self.put(i, fingerprint) {
+                return;
+            }
+        }
+        self.victim = Some((i, fingerprint));
+    }
+
+    #[inline]
+    fn next_random(&mut self) -> u64
+    {
+        self.random = self.random.wrapping_add(1);
+        H64::hash(self.random).0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64CuckooFilter
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("H64CuckooFilter")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+/// Count-min sketch of occurrences of [`H64`]s.
+///
+/// Estimates never undercount.
+/// With `width` columns, an estimate overcounts by at most
+/// `e / width` times the total count, with probability `1 - e^-depth`.
+/// The column of a [`H64`] in each row is derived from its hashed value
+/// by double hashing, like the probes of [`H64BloomFilter`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Eq, PartialEq)]
+pub struct H64CountMinSketch
+{
+    counters: Box<[u64]>,
+    width: usize,
+    depth: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl H64CountMinSketch
+{
+    /// Create an empty sketch with `depth` rows of `width` counters.
+    ///
+    /// Panics if either is zero.
+    pub fn new(width: usize, depth: usize) -> Self
+    {
+        assert!(width > 0 && depth > 0, "H64CountMinSketch must have rows and columns");
+        let len = width.checked_mul(depth).expect("H64CountMinSketch too large");
+        Self { counters: vec![0; len].into_boxed_slice(), width, depth }
+    }
+
+    /// Create an empty sketch whose estimates overcount
+    /// by at most `epsilon` times the total count,
+    /// except with probability `delta`.
+    ///
+    /// Panics unless both are strictly between zero and one.
+    #[cfg(feature = "std")]
+    pub fn with_error(epsilon: f64, delta: f64) -> Self
+    {
+        assert!(
+            0.0 < epsilon && epsilon < 1.0 && 0.0 < delta && delta < 1.0,
+            "H64CountMinSketch error bounds must be between 0 and 1",
+        );
+        let width = (core::f64::consts::E / epsilon).ceil();
+        let depth = (1.0 / delta).ln().ceil().max(1.0);
+        Self::new(width as usize, depth as usize)
+    }
+
+    /// The number of counters per row.
+    #[inline(always)]
+    pub fn width(&self) -> usize
+    {
+        self.width
+    }
+
+    /// The number of rows.
+    #[inline(always)]
+    pub fn depth(&self) -> usize
+    {
+        self.depth
+    }
+
+    /// Count one occurrence of a [`H64`].
+    #[inline]
+    pub fn increment(&mut self, h64: H64)
+    {
+        self.add(h64, 1);
+    }
+
+    /// Count `n` occurrences of a [`H64`].
+    ///
+    /// Counters saturate at [`u64::MAX`].
+    pub fn add(&mut self, h64: H64, n: u64)
+    {
+        for i in self.indices(h64) {
+            self.counters[i] = self.counters[i].saturating_add(n);
+        }
+    }
+
+    /// Estimate the number of occurrences of a [`H64`].
+    pub fn estimate(&self, h64: H64) -> u64
+    {
+        self.indices(h64).map(|i| self.counters[i]).min().unwrap_or(0)
+    }
+
+    /// Add the counts of another sketch to this one.
+    ///
+    /// Panics if the sketches have different dimensions.
+    pub fn merge(&mut self, other: &Self)
+    {
+        assert!(
+            self.width == other.width && self.depth == other.depth,
+            "H64CountMinSketch dimensions differ",
+        );
+        for (counter, other) in Iterator::zip(self.counters.iter_mut(), other.counters.iter()) {
+            *counter = counter.saturating_add(*other);
+        }
+    }
+
+    /// Reset all counts to zero.
+    #[inline]
+    pub fn clear(&mut self)
+    {
+        self.counters.fill(0);
+    }
+
+    fn indices(&self, h64: H64) -> impl Iterator<Item = usize>
+    {
+        let (h1, h2) = (h64.0, h64.0.rotate_left(32) | 1);
+        let width = self.width;
+        (0 .. self.depth).map(move |row| {
+            let h = u64::wrapping_add(h1, u64::wrapping_mul(row as u64, h2));
+            row * width + H64(h).partition(width)
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64CountMinSketch
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("H64CountMinSketch")
+            .field("width", &self.width)
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+/// HyperLogLog estimator of the number of distinct [`H64`]s.
+///
+/// The hashed value of each [`H64`] is used directly as the hash,
+/// so no further hashing is needed.
+/// With precision `p`, this takes `2^p` bytes
+/// and has a standard error of about `1.04 / sqrt(2^p)`.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Eq, PartialEq)]
+pub struct H64HyperLogLog
+{
+    registers: Box<[u8]>,
+    precision: u32,
+}
+
+#[cfg(feature = "alloc")]
+impl H64HyperLogLog
+{
+    /// Create an empty estimator with `2^precision` registers.
+    ///
+    /// Panics unless the precision is between 4 and 16.
+    pub fn new(precision: u32) -> Self
+    {
+        assert!((4 ..= 16).contains(&precision), "H64HyperLogLog precision must be between 4 and 16");
+        Self { registers: vec![0; 1 << precision].into_boxed_slice(), precision }
+    }
+
+    /// Recreate an estimator from its [`registers`](Self::registers).
+    ///
+    /// Returns [`None`] if the precision is out of range,
+    /// the number of registers does not match it,
+    /// or a register holds an impossible value.
+    pub fn from_registers(precision: u32, registers: Vec<u8>) -> Option<Self>
+    {
+        let valid = (4 ..= 16).contains(&precision)
+            && registers.len() == 1 << precision
+            && registers.iter().all(|&r| u32::from(r) <= 65 - precision);
+        valid.then(|| Self { registers: registers.into_boxed_slice(), precision })
+    }
+
+    /// The precision.
+    #[inline(always)]
+    pub fn precision(&self) -> u32
+    {
+        self.precision
+    }
+
+    /// The registers.
+    #[inline(always)]
+    pub fn registers(&self) -> &[u8]
+    {
+        &self.registers
+    }
+
+    /// Record a [`H64`].
+    #[inline]
+    pub fn insert(&mut self, h64: H64)
+    {
+        let index = (h64.0 >> (64 - self.precision)) as usize;
+        let rank = (h64.0 << self.precision | 1 << (self.precision - 1)).leading_zeros() + 1;
+        let register = &mut self.registers[index];
+        *register = (*register).max(rank as u8);
+    }
+
+    /// Estimate the number of distinct [`H64`]s recorded.
+    #[cfg(feature = "std")]
+    pub fn estimate(&self) -> f64
+    {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self.registers.iter().map(|&r| 1.0 / (1u64 << r) as f64).sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+
+    /// Record the [`H64`]s recorded by another estimator.
+    ///
+    /// Panics if the estimators have different precisions.
+    pub fn merge(&mut self, other: &Self)
+    {
+        assert_eq!(self.precision, other.precision, "H64HyperLogLog precisions differ");
+        for (register, other) in Iterator::zip(self.registers.iter_mut(), other.registers.iter()) {
+            *register = (*register).max(*other);
+        }
+    }
+
+    /// Forget all recorded [`H64`]s.
+    #[inline]
+    pub fn clear(&mut self)
+    {
+        self.registers.fill(0);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Extend<H64> for H64HyperLogLog
+{
+    fn extend<I: IntoIterator<Item = H64>>(&mut self, iter: I)
+    {
+        for h64 in iter {
+            self.insert(h64);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64HyperLogLog
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("H64HyperLogLog")
+            .field("precision", &self.precision)
+            .finish()
+    }
+}
+
+/// Tracker of the most frequent [`H64`]s in a stream.
+///
+/// This implements the space-saving algorithm,
+/// which keeps at most a fixed number of counters.
+/// When a new [`H64`] arrives and all counters are in use,
+/// the counter with the lowest count is given to the new [`H64`].
+/// Counts may therefore be overestimated, but never underestimated,
+/// and every [`H64`] that occurs more than
+/// [`max_error`](Self::max_error) times is tracked.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct H64TopK
+{
+    counters: H64HashMap<H64, (u64, u64)>,
+    capacity: usize,
+    total: u64,
+}
+
+#[cfg(feature = "alloc")]
+impl H64TopK
+{
+    /// Create an empty tracker with the given number of counters.
+    ///
+    /// Panics if the capacity is zero.
+    pub fn new(capacity: usize) -> Self
+    {
+        assert!(capacity > 0, "H64TopK capacity must be nonzero");
+        let counters = H64HashMap::with_capacity_and_hasher(capacity, Default::default());
+        Self { counters, capacity, total: 0 }
+    }
+
+    /// The maximum number of tracked [`H64`]s.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize
+    {
+        self.capacity
+    }
+
+    /// The number of tracked [`H64`]s.
+    #[inline(always)]
+    pub fn len(&self) -> usize
+    {
+        self.counters.len()
+    }
+
+    /// Whether no [`H64`]s have been offered.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool
+    {
+        self.counters.is_empty()
+    }
+
+    /// The number of [`H64`]s offered.
+    #[inline(always)]
+    pub fn total(&self) -> u64
+    {
+        self.total
+    }
+
+    /// The maximum amount by which any count is overestimated.
+    ///
+    /// This is the number of offered [`H64`]s divided by the capacity.
+    #[inline]
+    pub fn max_error(&self) -> u64
+    {
+        self.total / self.capacity as u64
+    }
+
+    /// Record an occurrence of a [`H64`].
+    ///
+    /// When all counters are in use and the [`H64`] is not tracked,
+    /// this takes time linear in the capacity.
+    pub fn offer(&mut self, h64: H64)
+    {
+        self.total = self.total.saturating_add(1);
+        if let Some((count, _)) = self.counters.get_mut(&h64) {
+            *count = count.saturating_add(1);
+        } else if self.counters.len() < self.capacity {
+            self.counters.insert(h64, (1, 0));
+        } else {
+            let (&evicted, &(min, _)) = self.counters.iter()
+                .min_by_key(|&(key, &(count, _))| (count, key.unhash()))
+                .expect("H64TopK has counters");
+            self.counters.remove(&evicted);
+            self.counters.insert(h64, (min.saturating_add(1), min));
+        }
+    }
+
+    /// The estimated count of a [`H64`] and the bound on its error.
+    ///
+    /// The true count lies between the estimate minus the error and the estimate.
+    /// Returns [`None`] if the [`H64`] is not tracked.
+    #[inline]
+    pub fn count(&self, h64: H64) -> Option<(u64, u64)>
+    {
+        self.counters.get(&h64).copied()
+    }
+
+    /// The `n` tracked [`H64`]s with the highest estimated counts.
+    ///
+    /// Each is returned with its estimated count and the bound on its error.
+    /// Ties are broken by the original values.
+    pub fn top(&self, n: usize) -> Vec<(H64, u64, u64)>
+    {
+        let mut top: Vec<_> = self.counters.iter()
+            .map(|(&key, &(count, error))| (key, count, error))
+            .collect();
+        top.sort_unstable_by_key(|&(key, count, _)| (cmp::Reverse(count), key.unhash()));
+        top.truncate(n);
+        top
+    }
+
+    /// Forget all offered [`H64`]s.
+    #[inline]
+    pub fn clear(&mut self)
+    {
+        self.counters.clear();
+        self.total = 0;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Extend<H64> for H64TopK
+{
+    fn extend<I: IntoIterator<Item = H64>>(&mut self, iter: I)
+    {
+        for h64 in iter {
+            self.offer(h64);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for H64TopK
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let top = self.top(self.capacity);
+        f.debug_map()
+            .entries(top.iter().map(|&(key, count, _)| (key, count)))
+            .finish()
+    }
+}
+
+/// Error returned when a fixed-capacity collection is full.
+///
+/// The error holds the value that could not be inserted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CapacityError<T>(T);
+
+impl<T> CapacityError<T>
+{
+    /// Create an error for a value that could not be inserted.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self
+    {
+        Self(value)
+    }
+
+    /// Obtain the value that could not be inserted.
+    #[inline(always)]
+    pub fn into_inner(self) -> T
+    {
+        self.0
+    }
+}
+
+impl<T> fmt::Display for CapacityError<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.write_str("collection is full")
+    }
+}
+
+impl<T: fmt::Debug> core::error::Error for CapacityError<T>
+{
+}
+
+pub mod rendezvous
+{
+    //! Rendezvous hashing, also known as highest random weight hashing.
+    //!
+    //! Every node gets a weight for every key,
+    //! and a key is assigned to the nodes with the highest weights.
+    //! When a node is removed, only the keys assigned to it move.
+    //!
+    //! ```
+    //! use h64::{H64, rendezvous};
+    //! let nodes = [H64::hash(1), H64::hash(2), H64::hash(3)];
+    //! let key = H64::hash(42);
+    //! let primary = rendezvous::select(key, &nodes).unwrap();
+    //! # #[cfg(feature = "alloc")]
+    //! assert_eq!(rendezvous::top_k(key, &nodes, 2)[0], primary);
+    //! ```
+
+    use super::*;
+
+    /// The weight of a node for a key.
+    ///
+    /// The key is mixed with the node using [`Murmur3`],
+    /// so that the order of the nodes differs from key to key.
+    /// Distinct nodes have distinct weights for the same key.
+    #[inline]
+    pub fn weight(key: H64, node: H64) -> u64
+    {
+        Murmur3::forward(key.0 ^ Murmur3::forward(node.0))
+    }
+
+    /// The node with the highest weight for a key.
+    ///
+    /// Returns [`None`] if there are no nodes.
+    pub fn select(key: H64, nodes: &[H64]) -> Option<H64>
+    {
+        nodes.iter().copied().max_by_key(|&node| weight(key, node))
+    }
+
+    /// The `k` nodes with the highest weights for a key.
+    ///
+    /// The nodes are returned in order of decreasing weight.
+    /// Fewer than `k` nodes are returned if there are fewer nodes.
+    #[cfg(feature = "alloc")]
+    pub fn top_k(key: H64, nodes: &[H64], k: usize) -> Vec<H64>
+    {
+        let mut nodes = nodes.to_vec();
+        nodes.sort_unstable_by_key(|&node| cmp::Reverse(weight(key, node)));
+        nodes.truncate(k);
+        nodes
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary
+{
+    //! Integration with [arbitrary](https://docs.rs/arbitrary).
+    //!
+    //! Values are generated from their original [`u64`],
+    //! so fuzzer input maps predictably onto IDs.
+    //! Types defined with [`define_h64_id!`] can derive [`Arbitrary`].
+
+    use {
+        super::*,
+        ::arbitrary::{Arbitrary, Result, Unstructured},
+    };
+
+    impl<'a> Arbitrary<'a> for H64
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self>
+        {
+            u64::arbitrary(u).map(Self::hash)
+        }
+
+        #[inline]
+        fn size_hint(depth: usize) -> (usize, Option<usize>)
+        {
+            u64::size_hint(depth)
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for NonZeroH64
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self>
+        {
+            NonZeroU64::arbitrary(u).map(Self::hash)
+        }
+
+        #[inline]
+        fn size_hint(depth: usize) -> (usize, Option<usize>)
+        {
+            NonZeroU64::size_hint(depth)
+        }
+    }
+
+    impl<'a, T: ?Sized> Arbitrary<'a> for H64Id<T>
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self>
+        {
+            H64::arbitrary(u).map(Self::new)
+        }
+
+        #[inline]
+        fn size_hint(depth: usize) -> (usize, Option<usize>)
+        {
+            H64::size_hint(depth)
+        }
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+pub mod bytemuck
+{
+    //! Integration with [bytemuck](https://docs.rs/bytemuck).
+    //!
+    //! The bytes of a [`H64`] are those of its hashed value.
+
+    use {
+        super::*,
+        ::bytemuck::{Pod, Zeroable},
+    };
+
+    // SAFETY: H64 is a transparent wrapper of u64.
+    unsafe impl Zeroable for H64
+    {
+    }
+
+    // SAFETY: H64 is a transparent wrapper of u64.
+    unsafe impl Pod for H64
+    {
+    }
+
+    // SAFETY: H64Id is a transparent wrapper of H64.
+    unsafe impl<T: ?Sized + 'static> Zeroable for H64Id<T>
+    {
+    }
+
+    // SAFETY: H64Id is a transparent wrapper of H64.
+    unsafe impl<T: ?Sized + 'static> Pod for H64Id<T>
+    {
+    }
+}
+
+#[cfg(feature = "dashmap")]
+pub mod dashmap
+{
+    //! Integration with [dashmap](https://docs.rs/dashmap).
+    //!
+    //! The constructors of [`DashMap`] and [`DashSet`] without a hasher argument
+    //! use the default hasher, so use the functions in this module instead.
+
+    use {
+        super::*,
+        ::dashmap::{DashMap, DashSet},
+    };
+
+    /// Specialization of [`DashMap`] that uses [`H64Hasher`].
+    pub type H64DashMap<K, V> = DashMap<K, V, BuildHasherDefault<H64Hasher>>;
+
+    /// Specialization of [`DashSet`] that uses [`H64Hasher`].
+    pub type H64DashSet<T> = DashSet<T, BuildHasherDefault<H64Hasher>>;
+
+    /// Create an empty [`H64DashMap`].
+    #[inline]
+    pub fn new_map<K: Eq + Hash, V>() -> H64DashMap<K, V>
+    {
+        DashMap::with_hasher(Default::default())
+    }
+
+    /// Create an empty [`H64DashMap`] with room for at least `capacity` entries.
+    #[inline]
+    pub fn map_with_capacity<K: Eq + Hash, V>(capacity: usize) -> H64DashMap<K, V>
+    {
+        DashMap::with_capacity_and_hasher(capacity, Default::default())
+    }
+
+    /// Create an empty [`H64DashSet`].
+    #[inline]
+    pub fn new_set<T: Eq + Hash>() -> H64DashSet<T>
+    {
+        DashSet::with_hasher(Default::default())
+    }
+
+    /// Create an empty [`H64DashSet`] with room for at least `capacity` elements.
+    #[inline]
+    pub fn set_with_capacity<T: Eq + Hash>(capacity: usize) -> H64DashSet<T>
+    {
+        DashSet::with_capacity_and_hasher(capacity, Default::default())
+    }
+}
+
+#[cfg(feature = "hashbrown")]
+pub mod hashbrown
+{
+    //! Integration with [hashbrown](https://docs.rs/hashbrown).
+    //!
+    //! These helpers look up entries by a precomputed [`H64`]
+    //! using hashbrown's raw entry API,
+    //! which is useful for keys that contain more than just the [`H64`].
+    //! The key must hash to the given [`H64`] (see [`H64Hasher`]),
+    //! which is the case if its [`Hash`] implementation only writes that [`H64`].
+
+    use {
+        super::*,
+        ::hashbrown::hash_map::RawEntryMut,
+    };
+
+    /// Find the entry whose key has the given hash and satisfies `is_match`.
+    #[inline]
+    pub fn get_by_hash<K, V, F>(map: &H64HashMap<K, V>, h64: H64, is_match: F) -> Option<(&K, &V)>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        map.raw_entry().from_hash(h64.into_raw(), is_match)
+    }
+
+    /// Find the entry whose key has the given hash and satisfies `is_match`,
+    /// or where to insert such an entry.
+    #[inline]
+    pub fn entry_by_hash<K, V, F>(map: &mut H64HashMap<K, V>, h64: H64, is_match: F)
+        -> RawEntryMut<'_, K, V, BuildHasherDefault<H64Hasher>>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        map.raw_entry_mut().from_hash(h64.into_raw(), is_match)
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub mod proptest
+{
+    //! Integration with [proptest](https://docs.rs/proptest).
+
+    use {
+        super::*,
+        ::proptest::{prelude::{Just, Strategy, any}, prop_oneof},
+    };
+
+    /// Strategy that generates arbitrary [`H64`]s.
+    ///
+    /// The original [`u64`]s 0 and [`u64::MAX`] are generated more often.
+    /// Shrinking shrinks the original [`u64`].
+    pub fn any_h64() -> impl Strategy<Value = H64>
+    {
+        prop_oneof![
+            1 => Just(0),
+            1 => Just(u64::MAX),
+            8 => any::<u64>(),
+        ]
+        .prop_map(H64::hash)
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+pub mod quickcheck
+{
+    //! Integration with [quickcheck](https://docs.rs/quickcheck).
+    //!
+    //! [`H64`]s are generated and shrunk by their original [`u64`].
+
+    use {
+        super::*,
+        ::quickcheck::{Arbitrary, Gen},
+    };
+
+    impl Arbitrary for H64
+    {
+        fn arbitrary(g: &mut Gen) -> Self
+        {
+            Self::hash(u64::arbitrary(g))
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>>
+        {
+            Box::new(self.unhash().shrink().map(Self::hash))
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+pub mod rand
+{
+    //! Integration with [rand](https://docs.rs/rand).
+    //!
+    //! Random [`H64`]s are uniformly distributed over all [`u64`]s.
+
+    use {
+        super::*,
+        ::rand::{Rng, distributions::{Distribution, Standard}},
+    };
+
+    impl Distribution<H64> for Standard
+    {
+        #[inline]
+        fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> H64
+        {
+            // The hash of a uniformly random u64 is uniformly random,
+            // so there is no need to compute it.
+            H64(rng.gen())
+        }
+    }
+
+    impl H64
+    {
+        /// Sample a random [`H64`].
+        #[inline]
+        pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self
+        {
+            rng.gen()
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+pub mod rayon
+{
+    //! Integration with [rayon](https://docs.rs/rayon).
+    //!
+    //! Parallel iterators collect into and extend [`H64HashMap`] and [`H64HashSet`]
+    //! (and their siblings for other widths) like any other [`HashMap`] and [`HashSet`].
+
+    use {
+        super::*,
+        ::rayon::{iter::{FromParallelIterator, ParallelExtend}, slice::ParallelSliceMut},
+    };
+
+    // Rayon supports maps and sets with any default hasher.
+    // Make sure that keeps covering ours.
+    const _: () = {
+        fn collectable<C, T: Send>()
+        where
+            C: FromParallelIterator<T> + ParallelExtend<T>,
+        {
+        }
+
+        #[allow(dead_code)]
+        fn check()
+        {
+            collectable::<H64HashMap<H64, ()>, (H64, ())>();
+            collectable::<H64HashSet<H64>, H64>();
+            collectable::<H32HashMap<H32, ()>, (H32, ())>();
+            collectable::<H32HashSet<H32>, H32>();
+            collectable::<H128HashMap<H128, ()>, (H128, ())>();
+            collectable::<H128HashSet<H128>, H128>();
+        }
+    };
+
+    /// Number of elements converted by each task.
+    ///
+    /// Large enough that the scheduling overhead is negligible.
+    const CHUNK_SIZE: usize = 1 << 16;
+
+    impl H64
+    {
+        /// Hash each [`u64`] in a slice, in parallel.
+        ///
+        /// See [`hash_slice`](Self::hash_slice).
+        pub fn par_hash_slice(is: &[u64]) -> Vec<Self>
+        {
+            let mut h64s = Self::cast_slice(is).to_vec();
+            Self::par_hash_slice_in_place(Self::raw_slice_mut(&mut h64s));
+            h64s
+        }
+
+        /// Hash each [`u64`] in a slice, reusing its memory, in parallel.
+        ///
+        /// See [`hash_slice_in_place`](Self::hash_slice_in_place).
+        pub fn par_hash_slice_in_place(is: &mut [u64]) -> &mut [Self]
+        {
+            is.par_chunks_mut(CHUNK_SIZE).for_each(|chunk| {
+                Self::hash_slice_in_place(chunk);
+            });
+            Self::cast_slice_mut(is)
+        }
+
+        /// Obtain the original [`u64`] of each [`H64`] in a slice, in parallel.
+        ///
+        /// See [`unhash_slice`](Self::unhash_slice).
+        pub fn par_unhash_slice(h64s: &[Self]) -> Vec<u64>
+        {
+            let mut is = Self::raw_slice(h64s).to_vec();
+            Self::par_unhash_slice_in_place(Self::cast_slice_mut(&mut is));
+            is
+        }
+
+        /// Obtain the original [`u64`] of each [`H64`] in a slice, reusing its memory, in parallel.
+        ///
+        /// See [`unhash_slice_in_place`](Self::unhash_slice_in_place).
+        pub fn par_unhash_slice_in_place(h64s: &mut [Self]) -> &mut [u64]
+        {
+            h64s.par_chunks_mut(CHUNK_SIZE).for_each(|chunk| {
+                Self::unhash_slice_in_place(chunk);
+            });
+            Self::raw_slice_mut(h64s)
+        }
+    }
+}
+
+#[cfg(feature = "rkyv")]
+pub mod rkyv
+{
+    //! Integration with [rkyv](https://rkyv.org).
+    //!
+    //! [`H64`] archives as its hashed value,
+    //! with the same layout as an archived [`u64`].
+
+    use {
+        super::*,
+        ::rkyv::{Archive, Archived, Deserialize, Fallible, Serialize, bytecheck::CheckBytes},
+    };
+
+    /// Archived [`H64`].
+    #[derive(Clone, Copy, Eq, Hash, PartialEq)]
+    #[repr(transparent)]
+    pub struct ArchivedH64(Archived<u64>);
+
+    impl ArchivedH64
+    {
+        /// Obtain the [`H64`].
+        #[inline]
+        pub fn get(&self) -> H64
+        {
+            H64(u64::from(self.0))
+        }
+    }
+
+    impl PartialEq<H64> for ArchivedH64
+    {
+        #[inline]
+        fn eq(&self, other: &H64) -> bool
+        {
+            self.get() == *other
+        }
+    }
+
+    impl fmt::Debug for ArchivedH64
+    {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+        {
+            fmt::Debug::fmt(&self.get(), f)
+        }
+    }
+
+    impl fmt::Display for ArchivedH64
+    {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+        {
+            fmt::Display::fmt(&self.get(), f)
+        }
+    }
+
+    impl Archive for H64
+    {
+        type Archived = ArchivedH64;
+        type Resolver = <u64 as Archive>::Resolver;
+
+        #[inline]
+        unsafe fn resolve(&self, pos: usize, resolver: Self::Resolver, out: *mut ArchivedH64)
+        {
+            // SAFETY: ArchivedH64 is a transparent wrapper of Archived<u64>.
+            self.0.resolve(pos, resolver, out.cast());
+        }
+    }
+
+    impl<S: Fallible + ?Sized> Serialize<S> for H64
+    {
+        #[inline]
+        fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error>
+        {
+            self.0.serialize(serializer)
+        }
+    }
+
+    impl<D: Fallible + ?Sized> Deserialize<H64, D> for ArchivedH64
+    {
+        #[inline]
+        fn deserialize(&self, _deserializer: &mut D) -> Result<H64, D::Error>
+        {
+            Ok(self.get())
+        }
+    }
+
+    impl<C: ?Sized> CheckBytes<C> for ArchivedH64
+    where
+        Archived<u64>: CheckBytes<C>,
+    {
+        type Error = <Archived<u64> as CheckBytes<C>>::Error;
+
+        unsafe fn check_bytes<'a>(value: *const Self, context: &mut C) -> Result<&'a Self, Self::Error>
+        {
+            // SAFETY: ArchivedH64 is a transparent wrapper of Archived<u64>.
+            Archived::<u64>::check_bytes(value.cast(), context)?;
+            Ok(&*value)
+        }
+    }
+}
+
+#[cfg(feature = "roaring")]
+pub mod roaring
+{
+    //! Integration with [roaring](https://docs.rs/roaring).
+
+    use {
+        super::*,
+        ::roaring::RoaringTreemap,
+    };
+
+    /// Set of [`H64`]s, stored by original [`u64`] in a [`RoaringTreemap`].
+    ///
+    /// Roaring bitmaps compress clustered values well,
+    /// such as [`H64`]s from an [`H64Allocator`].
+    #[derive(Clone, Default, Eq, PartialEq)]
+    pub struct H64RoaringSet(RoaringTreemap);
+
+    impl H64RoaringSet
+    {
+        /// Create an empty set.
+        #[inline]
+        pub fn new() -> Self
+        {
+            Self(RoaringTreemap::new())
+        }
+
+        /// The number of elements in the set.
+        #[inline]
+        pub fn len(&self) -> u64
+        {
+            self.0.len()
+        }
+
+        /// Whether the set has no elements.
+        #[inline]
+        pub fn is_empty(&self) -> bool
+        {
+            self.0.is_empty()
+        }
+
+        /// Insert an element, returning whether it was not yet in the set.
+        #[inline]
+        pub fn insert(&mut self, h64: H64) -> bool
+        {
+            self.0.insert(h64.unhash())
+        }
+
+        /// Whether an element is in the set.
+        #[inline]
+        pub fn contains(&self, h64: H64) -> bool
+        {
+            self.0.contains(h64.unhash())
+        }
+
+        /// Remove an element, returning whether it was in the set.
+        #[inline]
+        pub fn remove(&mut self, h64: H64) -> bool
+        {
+            self.0.remove(h64.unhash())
+        }
+
+        /// Remove all elements.
+        #[inline]
+        pub fn clear(&mut self)
+        {
+            self.0.clear();
+        }
+
+        /// Add the elements of another set to this one.
+        #[inline]
+        pub fn union_with(&mut self, other: &Self)
+        {
+            self.0 |= &other.0;
+        }
+
+        /// Remove the elements that are not in another set.
+        #[inline]
+        pub fn intersect_with(&mut self, other: &Self)
+        {
+            self.0 &= &other.0;
+        }
+
+        /// Remove the elements that are in another set.
+        #[inline]
+        pub fn difference_with(&mut self, other: &Self)
+        {
+            self.0 -= &other.0;
+        }
+
+        /// Whether all elements of this set are in another set.
+        #[inline]
+        pub fn is_subset(&self, other: &Self) -> bool
+        {
+            self.0.is_subset(&other.0)
+        }
+
+        /// Iterate over the elements in order of original [`u64`].
+        pub fn iter(&self) -> impl Iterator<Item = H64> + '_
+        {
+            self.0.iter().map(H64::hash)
+        }
+
+        /// The underlying set of original [`u64`]s.
+        #[inline(always)]
+        pub fn as_inner(&self) -> &RoaringTreemap
+        {
+            &self.0
+        }
+
+        /// The underlying set of original [`u64`]s.
+        #[inline(always)]
+        pub fn into_inner(self) -> RoaringTreemap
+        {
+            self.0
+        }
+    }
+
+    impl From<RoaringTreemap> for H64RoaringSet
+    {
+        #[inline(always)]
+        fn from(other: RoaringTreemap) -> Self
+        {
+            Self(other)
+        }
+    }
+
+    impl From<&H64HashSet<H64>> for H64RoaringSet
+    {
+        fn from(other: &H64HashSet<H64>) -> Self
+        {
+            other.iter().copied().collect()
+        }
+    }
+
+    impl From<&H64RoaringSet> for H64HashSet<H64>
+    {
+        fn from(other: &H64RoaringSet) -> Self
+        {
+            other.iter().collect()
+        }
+    }
+
+    impl Extend<H64> for H64RoaringSet
+    {
+        fn extend<I: IntoIterator<Item = H64>>(&mut self, iter: I)
+        {
+            self.0.extend(iter.into_iter().map(H64::unhash));
+        }
+    }
+
+    impl FromIterator<H64> for H64RoaringSet
+    {
+        fn from_iter<I: IntoIterator<Item = H64>>(iter: I) -> Self
+        {
+            Self(iter.into_iter().map(H64::unhash).collect())
+        }
+    }
+
+    impl fmt::Debug for H64RoaringSet
+    {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+        {
+            f.debug_set().entries(self.iter()).finish()
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub mod serde
+{
+    //! Integration with [serde](https://serde.rs).
+    //!
+    //! IDs serialize as their original [`u64`],
+    //! so serialized IDs do not depend on the hash function.
+    //! Use [`raw`] to serialize the hashed value instead.
+    //! Allocators serialize as their [`position`](H64Allocator::position).
+    //! [`H64Pool`] serializes as its position and the original [`u64`]s of its free list.
+    //! [`Interner`] serializes as its values, in order of handle.
+    //! [`H64BloomFilter`] serializes as its number of bits, number of hashes, and words.
+    //! [`H64HyperLogLog`] serializes as its precision and registers.
+
+    use {
+        super::*,
+        ::serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _},
+    };
+
+    impl Serialize for H64
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            self.unhash().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for H64
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            u64::deserialize(deserializer).map(Self::hash)
+        }
+    }
+
+    impl Serialize for NonZeroH64
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            self.unhash().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for NonZeroH64
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            NonZeroU64::deserialize(deserializer).map(Self::hash)
+        }
+    }
+
+    impl<T: ?Sized> Serialize for H64Id<T>
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            self.unhash().serialize(serializer)
+        }
+    }
+
+    impl<'de, T: ?Sized> Deserialize<'de> for H64Id<T>
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            u64::deserialize(deserializer).map(Self::hash)
+        }
+    }
+
+    pub mod raw
+    {
+        //! Serialize a [`H64`] as its hashed value.
+        //!
+        //! This avoids computing the hash when deserializing,
+        //! but ties the serialized form to the hash function.
+        //! Use with `#[serde(with = "h64::serde::raw")]`.
+
+        use super::*;
+
+        /// Serialize the hashed value of a [`H64`].
+        pub fn serialize<S: Serializer>(h64: &H64, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            h64.0.serialize(serializer)
+        }
+
+        /// Deserialize a [`H64`] from its hashed value.
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<H64, D::Error>
+        {
+            u64::deserialize(deserializer).map(H64)
+        }
+    }
+
+    pub mod map_as_string_keys
+    {
+        //! Serialize a map with [`H64`] keys as a map with string keys.
+        //!
+        //! Keys are written as their original [`u64`] in decimal,
+        //! because formats such as JSON do not allow integer keys.
+        //! Use with `#[serde(with = "h64::serde::map_as_string_keys")]`.
+
+        use {
+            super::*,
+            ::serde::de::{MapAccess, Visitor},
+            core::iter,
+        };
+
+        /// Serialize a map with string keys.
+        pub fn serialize<'a, M, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            &'a M: IntoIterator<Item = (&'a H64, &'a V)>,
+            V: Serialize + 'a,
+            S: Serializer,
+        {
+            serializer.collect_map(map.into_iter().map(|(k, v)| (Key(*k), v)))
+        }
+
+        /// Deserialize a map with string keys.
+        pub fn deserialize<'de, M, V, D>(deserializer: D) -> Result<M, D::Error>
+        where
+            M: FromIterator<(H64, V)>,
+            V: Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_map(MapVisitor(PhantomData))
+        }
+
+        struct MapVisitor<M, V>(PhantomData<fn() -> (M, V)>);
+
+        impl<'de, M, V> Visitor<'de> for MapVisitor<M, V>
+        where
+            M: FromIterator<(H64, V)>,
+            V: Deserialize<'de>,
+        {
+            type Value = M;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result
+            {
+                f.write_str("a map with decimal string keys")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<M, A::Error>
+            {
+                iter::from_fn(|| map.next_entry::<Key, V>().transpose())
+                    .map(|entry| entry.map(|(Key(k), v)| (k, v)))
+                    .collect()
+            }
+        }
+
+        /// [`H64`] that serializes as a decimal string.
+        struct Key(H64);
+
+        impl Serialize for Key
+        {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            {
+                serializer.collect_str(&self.0.unhash())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for Key
+        {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+            {
+                deserializer.deserialize_str(KeyVisitor)
+            }
+        }
+
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor
+        {
+            type Value = Key;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result
+            {
+                f.write_str("a decimal string")
+            }
+
+            fn visit_str<E: ::serde::de::Error>(self, v: &str) -> Result<Key, E>
+            {
+                v.parse().map(|i| Key(H64::hash(i))).map_err(E::custom)
+            }
+
+            fn visit_u64<E: ::serde::de::Error>(self, v: u64) -> Result<Key, E>
+            {
+                Ok(Key(H64::hash(v)))
+            }
+        }
+    }
+
+    impl Serialize for H64Allocator
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            self.position().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for H64Allocator
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            u64::deserialize(deserializer).map(Self::resume)
+        }
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    impl Serialize for AtomicH64Allocator
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            self.position().serialize(serializer)
+        }
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    impl<'de> Deserialize<'de> for AtomicH64Allocator
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            u64::deserialize(deserializer).map(Self::resume)
+        }
+    }
+
+    impl<T: ?Sized> Serialize for H64IdAllocator<T>
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            self.0.serialize(serializer)
+        }
+    }
+
+    impl<'de, T: ?Sized> Deserialize<'de> for H64IdAllocator<T>
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            let allocator = H64Allocator::deserialize(deserializer)?;
+            Ok(Self(allocator, PhantomData))
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Serialize for H64Pool
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            let freed: Vec<u64> = self.freed().iter().map(|h64| h64.unhash()).collect();
+            (self.position(), freed).serialize(serializer)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<'de> Deserialize<'de> for H64Pool
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            let (position, freed) = <(u64, Vec<u64>)>::deserialize(deserializer)?;
+            let mut seen = H64HashSet::default();
+            let freed: Vec<H64> = freed.into_iter().map(H64::hash).collect();
+            for &h64 in &freed {
+                if h64.unhash() >= position || !seen.insert(h64) {
+                    return Err(D::Error::custom("invalid H64Pool free list"));
+                }
+            }
+            Ok(H64Pool::resume(position, freed))
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Serialize for H64BloomFilter
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            (self.num_bits(), self.num_hashes(), self.words()).serialize(serializer)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<'de> Deserialize<'de> for H64BloomFilter
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            let (num_bits, num_hashes, words) = <(u64, u32, Vec<u64>)>::deserialize(deserializer)?;
+            H64BloomFilter::from_words(num_bits, num_hashes, words)
+                .ok_or_else(|| D::Error::custom("invalid H64BloomFilter"))
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Serialize for H64HyperLogLog
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            (self.precision(), self.registers()).serialize(serializer)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<'de> Deserialize<'de> for H64HyperLogLog
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            let (precision, registers) = <(u32, Vec<u8>)>::deserialize(deserializer)?;
+            H64HyperLogLog::from_registers(precision, registers)
+                .ok_or_else(|| D::Error::custom("invalid H64HyperLogLog"))
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: ?Sized + Serialize> Serialize for Interner<T>
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        {
+            serializer.collect_seq(sorted_iter(&self.values).map(|(_, value)| &**value))
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<'de, T: ?Sized + Hash + Eq> Deserialize<'de> for Interner<T>
+    where
+        Box<T>: Deserialize<'de>,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+        {
+            let values = Vec::<Box<T>>::deserialize(deserializer)?;
+            let len = values.len();
+            let interner: Self = values.into_iter().collect();
+            if interner.len() != len {
+                return Err(D::Error::custom("duplicate value in Interner"));
+            }
+            Ok(interner)
+        }
+    }
+}
+
+#[cfg(feature = "zerocopy")]
+pub mod zerocopy
+{
+    //! Integration with [zerocopy](https://docs.rs/zerocopy).
+    //!
+    //! [`H64`] has the layout of its hashed value.
+    //! [`H64Le`] stores the hashed value unaligned and little-endian,
+    //! for use in packed file formats and network protocols.
+
+    use {
+        super::*,
+        ::zerocopy::{AsBytes, FromBytes, FromZeroes, Unaligned, byteorder::{LittleEndian, U64}},
+    };
+
+    /// Unaligned little-endian [`H64`].
+    #[derive(AsBytes, Clone, Copy, Eq, FromBytes, FromZeroes, Hash, PartialEq, Unaligned)]
+    #[repr(transparent)]
+    pub struct H64Le(U64<LittleEndian>);
+
+    impl H64Le
+    {
+        /// Create a [`H64Le`] from a [`H64`].
+        #[inline]
+        pub const fn new(h64: H64) -> Self
+        {
+            Self(U64::from_bytes(h64.0.to_le_bytes()))
+        }
+
+        /// Obtain the [`H64`].
+        #[inline]
+        pub fn get(self) -> H64
+        {
+            H64(self.0.get())
+        }
+    }
+
+    impl From<H64> for H64Le
+    {
+        #[inline(always)]
+        fn from(other: H64) -> Self
+        {
+            Self::new(other)
+        }
+    }
+
+    impl From<H64Le> for H64
+    {
+        #[inline(always)]
+        fn from(other: H64Le) -> Self
+        {
+            other.get()
+        }
+    }
+
+    impl fmt::Debug for H64Le
+    {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+        {
+            fmt::Debug::fmt(&self.get(), f)
+        }
+    }
+
+    impl fmt::Display for H64Le
+    {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+        {
+            fmt::Display::fmt(&self.get(), f)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn test_values() -> impl Iterator<Item = u64>
+    {
+        Iterator::chain(0 .. 1000, u64::MAX - 1000 .. u64::MAX)
+    }
+
+    #[test]
+    fn unhash_undos_hash()
+    {
+        for i in test_values() {
+            assert_eq!(H64::hash(i).unhash(), i);
+        }
+    }
+
+    #[test]
+    fn unhash_undos_hash_32()
+    {
+        for i in test_values() {
+            let i = i as u32;
+            assert_eq!(H32::hash(i).unhash(), i);
+        }
+    }
+
+    #[test]
+    fn unhash_undos_hash_128()
+    {
+        for i in test_values() {
+            for i in [u128::from(i), u128::from(i) << 64 | u128::from(!i)] {
+                assert_eq!(H128::hash(i).unhash(), i);
+            }
+        }
+    }
+
+    #[test]
+    fn non_zero_h64_agrees_with_h64()
+    {
+        use core::mem::size_of;
+        assert_eq!(size_of::<Option<NonZeroH64>>(), size_of::<H64>());
+        assert_eq!(NonZeroH64::new(H64::hash(0)), None);
+        for i in test_values().filter_map(NonZeroU64::new) {
+            let h64 = NonZeroH64::hash(i);
+            assert_eq!(h64.unhash(), i);
+            assert_eq!(h64.get(), H64::hash(i.get()));
+            assert_eq!(NonZeroH64::new(h64.get()), Some(h64));
+
+            let mut hasher = H64Hasher::default();
+            Hash::hash(&h64, &mut hasher);
+            assert_eq!(hasher.finish(), h64.get().0);
+        }
+    }
+
+    #[test]
+    fn h64_id_behaves_like_h64()
+    {
+        struct User;
+        let mut allocator = H64Allocator::new();
+        let mut id_allocator = H64IdAllocator::<User>::new();
+        for _ in test_values() {
+            let h64 = allocator.alloc();
+            let id = id_allocator.alloc();
+            assert_eq!(id.get(), h64);
+            assert_eq!(id, H64Id::hash(h64.unhash()));
+            assert_eq!(format!("{id:?} {id}"), format!("{h64:?} {h64}"));
+
+            let mut hasher = H64Hasher::default();
+            Hash::hash(&id, &mut hasher);
+            assert_eq!(hasher.finish(), h64.0);
+        }
+    }
+
+    #[test]
+    fn define_h64_id_formats_with_prefix()
+    {
+        crate::define_h64_id!(PlainId);
+        crate::define_h64_id!(
+            /// Doc comments are allowed.
+            pub(crate) UserId, prefix = "usr_",
+        );
+
+        let mut allocator = H64Allocator::new();
+        let plain = PlainId::alloc(&mut allocator);
+        let user = UserId::alloc(&mut allocator);
+        assert_eq!(H64::from(plain), H64::hash(0));
+        assert_eq!(UserId::from(H64::hash(1)), user);
+        assert_eq!(user.unhash(), 1);
+        assert_eq!(format!("{plain} {plain:?}"), "0 PlainId(0)");
+        assert_eq!(format!("{user} {user:?}"), "usr_1 UserId(1)");
+        assert_eq!("0".parse(), Ok(plain));
+        assert_eq!("usr_1".parse(), Ok(user));
+        assert_eq!("1".parse::<UserId>().unwrap_err().kind(), H64ParseErrorKind::Prefix);
+        assert_eq!("usr_x".parse::<UserId>().unwrap_err().kind(), H64ParseErrorKind::InvalidDigit);
+
+        let mut hasher = H64Hasher::default();
+        Hash::hash(&user, &mut hasher);
+        assert_eq!(hasher.finish(), H64::from(user).0);
+    }
+
+    #[test]
+    fn hash_is_const()
+    {
+        const TABLE: [H64; 3] = {
+            let mut allocator = H64Allocator::new();
+            [allocator.alloc(), allocator.alloc(), H64::hash(H64::hash(7).unhash())]
+        };
+        assert_eq!(TABLE, [H64::hash(0), H64::hash(1), H64::hash(7)]);
+    }
+
+    #[test]
+    fn h64_macro_is_usable_in_patterns()
+    {
+        const ROOT: H64 = crate::h64!(0);
+        const ADMIN: H64 = crate::h64!(u64::MAX - 1);
+        for i in test_values() {
+            let expected = i == 0 || i == u64::MAX - 1;
+            assert_eq!(matches!(H64::hash(i), ROOT | ADMIN), expected);
+        }
+        assert_eq!(crate::h64!(42), H64::hash(42));
+    }
+
+    #[test]
+    fn unhash_with_undos_hash_with()
+    {
+        let key = H64Key::new(0xDEADBEEF);
+        let other_key = H64Key::new(0xDEADBEEE);
+        for i in test_values() {
+            let keyed = H64Keyed::hash_with(&key, i);
+            assert_eq!(keyed.unhash_with(&key), i);
+            assert_eq!(H64Keyed::from_hashed(keyed.hashed()), keyed);
+            assert_ne!(keyed.hashed(), H64::hash(i).0);
+            assert_ne!(keyed, H64Keyed::hash_with(&other_key, i));
+        }
+    }
+
+    #[test]
+    fn bijections_are_invertible()
+    {
+        fn check<B: Bijection>()
+        {
+            for i in test_values() {
+                let h64 = H64Custom::<B>::hash(i);
+                assert_eq!(h64.unhash(), i);
+
+                let mut hasher = H64Hasher::default();
+                Hash::hash(&h64, &mut hasher);
+                assert_eq!(hasher.finish(), B::forward(i));
+            }
+        }
+        check::<SplitMix>();
+        check::<Murmur3>();
+        check::<Xxh3>();
+        assert_eq!(H64Custom::<SplitMix>::hash(42).0, H64::hash(42).0);
+    }
+
+    #[test]
+    fn inverse_multiplier_inverts()
+    {
+        const _: () = assert!(inverse_multiplier(0xBF58476D1CE4E5B9) == 0x96DE1B173F119089);
+        const _: () = assert!(inverse_multiplier(0x94D049BB133111EB) == 0x319642B2D24D8EC3);
+        const _: () = assert!(inverse_multiplier(0xFF51AFD7ED558CCD) == 0x4F74430C22A54005);
+        const _: () = assert!(inverse_multiplier(0xC4CEB9FE1A85EC53) == 0x9CB4B2F8129337DB);
+        const _: () = assert!(inverse_multiplier(0x165667919E3779F9) == 0x08DA8EE41D6DF849);
+        for i in test_values() {
+            let m = i | 1;
+            assert_eq!(u64::wrapping_mul(m, inverse_multiplier(m)), 1);
+        }
+    }
+
+    #[test]
+    fn xor_shift_multiply_matches_split_mix()
+    {
+        type Custom = XorShiftMultiply<30, 0xBF58476D1CE4E5B9, 27, 0x94D049BB133111EB, 31>;
+        type Other = XorShiftMultiply<29, 0x0123456789ABCDEF, 5, 0xFEDCBA9876543211, 60>;
+        for i in test_values() {
+            assert_eq!(Custom::forward(i), H64::hash(i).0);
+            assert_eq!(Custom::inverse(Custom::forward(i)), i);
+            assert_eq!(Other::inverse(Other::forward(i)), i);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn atomic_allocator_allocates_uniquely()
+    {
+        let allocator = AtomicH64Allocator::new();
+        let allocated: Vec<Vec<H64>> = std::thread::scope(|s| {
+            let threads: Vec<_> = (0 .. 4)
+                .map(|_| s.spawn(|| (0 .. 1000).map(|_| allocator.alloc()).collect()))
+                .collect();
+            threads.into_iter().map(|t| t.join().unwrap()).collect()
+        });
+        let unique: H64HashSet<H64> = allocated.iter().flatten().copied().collect();
+        assert_eq!(unique, (0 .. 4000).map(H64::hash).collect());
+    }
+
+    #[test]
+    fn allocator_starts_at_offset()
+    {
+        let mut allocator = H64Allocator::starting_at(1000);
+        assert_eq!(allocator.position(), 1000);
+        assert_eq!(allocator.alloc(), H64::hash(1000));
+        assert_eq!(allocator.position(), 1001);
+
+        let allocator = AtomicH64Allocator::starting_at(1000);
+        assert_eq!(allocator.alloc(), H64::hash(1000));
+        assert_eq!(allocator.position(), 1001);
+    }
+
+    #[test]
+    fn alloc_range_bumps_counter_once()
+    {
+        let mut allocator = H64Allocator::new();
+        allocator.alloc();
+        let range = allocator.alloc_range(1000);
+        assert_eq!(allocator.position(), 1001);
+        assert_eq!(allocator.alloc(), H64::hash(1001));
+        assert!(range.eq((1 .. 1001).map(H64::hash)));
+        assert_eq!(allocator.alloc_range(2).next_back(), Some(H64::hash(1003)));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn pool_recycles_freed()
+    {
+        let mut pool = H64Pool::new();
+        let a = pool.alloc();
+        let b = pool.alloc();
+        pool.free(a);
+        pool.free(b);
+        assert_eq!(pool.alloc(), b);
+        assert_eq!(pool.alloc(), a);
+        assert_eq!(pool.alloc(), H64::hash(2));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "H64 freed twice"]
+    fn pool_rejects_double_free()
+    {
+        let mut pool = H64Pool::new();
+        let a = pool.alloc();
+        pool.free(a);
+        pool.free(a);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn guard_frees_on_drop()
+    {
+        let pool = RefCell::new(H64Pool::new());
+        let leaked = H64Guard::new(&pool).leak();
+        let dropped = H64Guard::new(&pool).get();
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let guard = H64Guard::new(&pool);
+            panic!("{guard:?}");
+        }));
+        assert!(panicked.is_err());
+        let mut pool = pool.into_inner();
+        assert_eq!([pool.alloc(), pool.alloc()], [dropped, H64::hash(2)]);
+        assert_ne!(pool.alloc(), leaked);
+    }
+
+    #[test]
+    fn allocators_detect_exhaustion()
+    {
+        let mut allocator = H64Allocator::starting_at(u64::MAX - 2);
+        assert_eq!(allocator.try_alloc_range(3).map(Iterator::count), None);
+        assert_eq!(allocator.try_alloc(), Some(H64::hash(u64::MAX - 2)));
+        assert_eq!(allocator.alloc(), H64::hash(u64::MAX - 1));
+        assert!(allocator.is_exhausted());
+        assert_eq!(allocator.try_alloc(), None);
+        assert_eq!(allocator.try_alloc_range(0).map(Iterator::count), Some(0));
+
+        let allocator = AtomicH64Allocator::starting_at(u64::MAX - 1);
+        assert_eq!(allocator.try_alloc(), Some(H64::hash(u64::MAX - 1)));
+        assert!(allocator.is_exhausted());
+        assert_eq!(allocator.try_alloc(), None);
+        assert_eq!(allocator.position(), u64::MAX);
+    }
+
+    #[test]
+    #[should_panic = "H64Allocator exhausted"]
+    fn alloc_panics_on_exhaustion()
+    {
+        H64Allocator::starting_at(u64::MAX).alloc();
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn allocators_resume_exactly()
+    {
+        let mut allocator = H64Allocator::new();
+        allocator.alloc_range(10);
+        let mut resumed = H64Allocator::resume(allocator.position());
+        assert_eq!(resumed.alloc(), allocator.alloc());
+
+        let allocator = AtomicH64Allocator::starting_at(10);
+        allocator.alloc();
+        let resumed = AtomicH64Allocator::resume(allocator.position());
+        assert_eq!(resumed.alloc(), allocator.alloc());
+
+        let mut pool = H64Pool::new();
+        let h64s: Vec<_> = (0 .. 5).map(|_| pool.alloc()).collect();
+        pool.free(h64s[3]);
+        pool.free(h64s[1]);
+        let mut resumed = H64Pool::resume(pool.position(), pool.freed().to_vec());
+        for _ in 0 .. 3 {
+            assert_eq!(resumed.alloc(), pool.alloc());
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn generational_allocator_bumps_generation()
+    {
+        let mut allocator = GenerationalAllocator::new();
+        let a = allocator.alloc();
+        let b = allocator.alloc();
+        assert_eq!((a.index(), a.generation(), b.index()), (0, 0, 1));
+        assert_eq!(a.get(), H64::hash(0));
+        assert_eq!(b.get(), H64::hash(1));
+
+        assert!(allocator.free(a));
+        assert!(!allocator.free(a));
+        assert!(!allocator.is_allocated(a));
+        let c = allocator.alloc();
+        assert_eq!((c.index(), c.generation()), (0, 1));
+        assert_eq!(c.get(), H64::hash(1 << 32));
+        assert!(allocator.is_allocated(c));
+        assert!(!allocator.is_allocated(GenerationalH64::new(7, 0)));
+        assert_eq!(format!("{c:?}"), "GenerationalH64 { index: 0, generation: 1 }");
+    }
+
+    #[test]
+    fn local_allocators_lease_blocks()
+    {
+        let shared = AtomicH64Allocator::starting_at(u64::MAX - 7);
+        let mut a = LocalH64Allocator::new(&shared, 3);
+        let mut b = LocalH64Allocator::new(&shared, 3);
+        let allocated = [a.alloc(), b.alloc(), a.alloc(), b.alloc(), a.alloc(), a.alloc()];
+        let expected = [0, 3, 1, 4, 2, 6].map(|i| H64::hash(u64::MAX - 7 + i));
+        assert_eq!(allocated, expected);
+        assert_eq!(shared.position(), u64::MAX);
+        assert_eq!([b.try_alloc(), b.try_alloc()], [Some(H64::hash(u64::MAX - 2)), None]);
+        assert_eq!(shared.try_alloc_range(1).map(Iterator::count), None);
+    }
+
+    #[test]
+    fn shared_allocators_share_counter()
+    {
+        let mut memory = [0u64; 2];
+        let counter = &mut memory[1] as *mut u64;
+        // SAFETY: The counter is aligned and only accessed through the allocators.
+        let [a, b] = [(); 2].map(|()| unsafe { SharedH64Allocator::from_ptr(counter) });
+        assert_eq!([a.alloc(), b.alloc(), a.alloc()], [0, 1, 2].map(H64::hash));
+        let mut local = LocalH64Allocator::new(b, 10);
+        assert_eq!(local.alloc(), H64::hash(3));
+        assert_eq!(a.alloc_range(2).collect::<Vec<_>>(), [13, 14].map(H64::hash));
+        assert_eq!(memory, [0, 15]);
+    }
+
+    #[test]
+    #[cfg(feature = "global")]
+    fn alloc_global_is_unique()
+    {
+        let allocated: H64HashSet<H64> = (0 .. 1000).map(|_| alloc_global()).collect();
+        assert_eq!(allocated.len(), 1000);
+    }
+
+    #[test]
+    fn allocator_is_iterator()
+    {
+        let mut allocator = H64Allocator::new();
+        let taken: Vec<H64> = allocator.by_ref().take(3).collect();
+        assert_eq!(taken, [0, 1, 2].map(H64::hash));
+        assert_eq!(allocator.alloc(), H64::hash(3));
+
+        let allocator = H64Allocator::starting_at(u64::MAX - 2);
+        assert_eq!(allocator.size_hint(), (2, Some(2)));
+        assert!(allocator.eq([u64::MAX - 2, u64::MAX - 1].map(H64::hash)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn snowflake_generator_packs_fields()
+    {
+        let layout = H64SnowflakeLayout::new(8, 2, 2);
+        let mut generator = H64SnowflakeGenerator::new(layout, 3, SystemTime::UNIX_EPOCH);
+        let at = |t: u64, s: u64| Some(H64::hash(t << 4 | 3 << 2 | s));
+        assert_eq!(generator.generate_at(5), at(5, 0));
+        assert_eq!(generator.generate_at(5), at(5, 1));
+        assert_eq!(generator.generate_at(4), at(5, 2));
+        assert_eq!(generator.generate_at(5), at(5, 3));
+        assert_eq!(generator.generate_at(5), None);
+        assert_eq!(generator.generate_at(6), at(6, 0));
+        assert_eq!(generator.generate_at(256), None);
+
+        let mut generator = H64SnowflakeGenerator::new(
+            H64SnowflakeLayout::TWITTER, 0, SystemTime::now());
+        let a = generator.generate().unhash();
+        let b = generator.generate().unhash();
+        assert!(a < b);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn snowflake_layouts_with_full_width_fields()
+    {
+        let epoch = SystemTime::UNIX_EPOCH;
+        let layout = H64SnowflakeLayout::new(0, 0, 64);
+        let mut generator = H64SnowflakeGenerator::new(layout, 0, epoch);
+        assert_eq!(generator.generate_at(0), Some(H64::hash(0)));
+        let decoded = layout.decode(H64::hash(u64::MAX)).unwrap();
+        assert_eq!((decoded.timestamp(), decoded.machine_id()), (0, 0));
+        assert_eq!(decoded.sequence(), u64::MAX);
+
+        let layout = H64SnowflakeLayout::new(0, 64, 0);
+        let mut generator = H64SnowflakeGenerator::new(layout, u64::MAX, epoch);
+        assert_eq!(generator.generate_at(0), Some(H64::hash(u64::MAX)));
+        assert_eq!(layout.decode(H64::hash(u64::MAX)).unwrap().machine_id(), u64::MAX);
+
+        let layout = H64SnowflakeLayout::new(64, 0, 0);
+        let mut generator = H64SnowflakeGenerator::new(layout, 0, epoch);
+        assert_eq!(generator.generate_at(7), Some(H64::hash(7)));
+        assert_eq!(layout.decode(H64::hash(7)).unwrap().timestamp(), 7);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn structured_ids_decode()
+    {
+        let layout = H64SnowflakeLayout::TWITTER;
+        let epoch = SystemTime::UNIX_EPOCH;
+        let mut generator = H64SnowflakeGenerator::new(layout, 1023, epoch);
+        generator.generate_at(41);
+        let h64 = generator.generate_at(42).unwrap();
+        let snowflake = generator.layout().decode(h64).unwrap();
+        assert_eq!(snowflake.timestamp(), 42);
+        assert_eq!(snowflake.machine_id(), 1023);
+        assert_eq!(snowflake.sequence(), 0);
+        assert_eq!(snowflake.system_time(generator.epoch()), epoch + Duration::from_millis(42));
+        assert_eq!(layout.decode(H64::hash(u64::MAX)), None);
+        assert!(H64SnowflakeLayout::new(0, 32, 32).decode(H64::hash(u64::MAX)).is_some());
+
+        let id = GenerationalH64::new(7, 3);
+        let decoded = GenerationalH64::from(id.get());
+        assert_eq!((decoded.index(), decoded.generation()), (7, 3));
+    }
+
+    #[test]
+    fn from_raw_undos_into_raw()
+    {
+        for i in test_values() {
+            let h64 = H64::hash(i);
+            assert_eq!(H64::from_raw(h64.into_raw()), h64);
+            assert_eq!(H64::from_raw(i).into_raw(), i);
+        }
+    }
+
+    #[test]
+    fn cast_slice_reinterprets()
+    {
+        let mut hashed: Vec<u64> = test_values().collect();
+        let h64s = H64::cast_slice(&hashed);
+        assert!(Iterator::eq(h64s.iter().map(|h| h.into_raw()), test_values()));
+        assert_eq!(H64::raw_slice(h64s), &hashed[..]);
+
+        H64::cast_slice_mut(&mut hashed)[0] = H64::hash(1);
+        assert_eq!(hashed[0], H64::hash(1).into_raw());
+        H64::raw_slice_mut(H64::cast_slice_mut(&mut hashed))[0] = 2;
+        assert_eq!(hashed[0], 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn unhash_slice_undos_hash_slice()
+    {
+        let is: Vec<u64> = test_values().collect();
+        let h64s = H64::hash_slice(&is);
+        assert!(Iterator::eq(h64s.iter().copied(), test_values().map(H64::hash)));
+        assert_eq!(H64::unhash_slice(&h64s), is);
+
+        let mut buffer = is.clone();
+        let h64s_in_place = H64::hash_slice_in_place(&mut buffer);
+        assert_eq!(h64s_in_place, &h64s[..]);
+        assert_eq!(H64::unhash_slice_in_place(h64s_in_place), &is[..]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn hash_slice_matches_hash()
+    {
+        // Cover lengths that are not multiples of any vector width.
+        for n in 0 .. 40 {
+            let is: Vec<u64> = test_values().skip(990).take(n).collect();
+            let h64s = H64::hash_slice(&is);
+            assert!(Iterator::eq(h64s.iter().copied(), is.iter().map(|&i| H64::hash(i))));
+            assert_eq!(H64::unhash_slice(&h64s), is);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn map_hashes_keys()
+    {
+        let mut map: H64Map<&str> = [(1, "one"), (2, "two")].into_iter().collect();
+        assert_eq!(map.insert(3, "three"), None);
+        assert_eq!(map.insert(1, "uno"), Some("one"));
+        assert_eq!(map.get(1), Some(&"uno"));
+        assert_eq!(map.as_inner().get(&H64::hash(2)), Some(&"two"));
+        assert_eq!(map.remove(2), Some("two"));
+        assert!(!map.contains_key(2));
+        let mut keys: Vec<u64> = map.keys().collect();
+        keys.sort();
+        assert_eq!(keys, [1, 3]);
+        assert_eq!(map.len(), 2);
+        map.clear();
+        assert!(map.is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn set_hashes_elements()
+    {
+        let mut set: H64Set = test_values().collect();
+        assert!(!set.insert(0));
+        assert!(set.contains(999));
+        assert!(set.as_inner().contains(&H64::hash(999)));
+        assert!(set.remove(999));
+        assert!(!set.contains(999));
+        assert_eq!(set.len(), 1999);
+        assert_eq!(set.iter().max(), Some(u64::MAX - 1));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn extension_traits_hash_values()
+    {
+        let mut map = H64HashMap::default();
+        assert_eq!(map.insert_value(1, "one"), None);
+        assert_eq!(map.get(&H64::hash(1)), Some(&"one"));
+        assert_eq!(map.get_value(1), Some(&"one"));
+        *map.get_value_mut(1).unwrap() = "uno";
+        assert_eq!(map.remove_value(1), Some("uno"));
+        assert!(!map.contains_value(1));
+
+        let mut set = H64HashSet::default();
+        assert!(set.insert_value(2));
+        assert!(set.contains(&H64::hash(2)));
+        assert!(set.contains_value(2));
+        assert!(set.remove_value(2));
+        assert!(set.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn sharded_map_spreads_keys()
+    {
+        let map = H64ShardedMap::new();
+        thread::scope(|scope| {
+            for t in 0 .. 4 {
+                let map = &map;
+                scope.spawn(move || {
+                    for i in (t .. 1000).step_by(4) {
+                        assert_eq!(map.insert(H64::hash(i), i), None);
+                    }
+                });
+            }
+        });
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&H64::hash(42)), Some(42));
+        assert_eq!(map.remove(&H64::hash(42)), Some(42));
+        assert!(!map.contains_key(&H64::hash(42)));
+        assert!(map.shards.iter().all(|shard| shard.read().unwrap().len() > 30));
+
+        let mut sum = 0;
+        map.for_each(|_, &v| sum += v);
+        assert_eq!(sum, 999 * 1000 / 2 - 42);
+
+        let map = H64ShardedMap::with_shards(1);
+        map.insert(H64::hash(1), ());
+        assert_eq!(map.len(), 1);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn atomic_set_fills_up()
+    {
+        let set = H64AtomicSet::with_capacity(100);
+        assert_eq!(set.capacity(), 128);
+        std::thread::scope(|scope| {
+            for _ in 0 .. 4 {
+                scope.spawn(|| {
+                    for i in 1 ..= 128 {
+                        set.insert(H64::hash(i)).unwrap();
+                    }
+                });
+            }
+        });
+        assert!((1 ..= 128).all(|i| set.contains(H64::hash(i))));
+        assert!(!set.contains(H64::hash(0)));
+        assert_eq!(set.insert(H64::hash(0)), Ok(true));
+        assert_eq!(set.insert(H64::hash(0)), Ok(false));
+        assert_eq!(set.insert(H64::hash(129)), Err(CapacityError::new(H64::hash(129))));
+        assert!(!set.contains(H64::hash(129)));
+    }
+
+    #[test]
+    fn array_map_scans_entries()
+    {
+        let mut map = H64ArrayMap::<&str, 2>::new();
+        assert_eq!(map.insert(H64::hash(1), "one"), Ok(None));
+        assert_eq!(map.insert(H64::hash(2), "two"), Ok(None));
+        assert_eq!(map.insert(H64::hash(1), "uno"), Ok(Some("one")));
+        assert_eq!(
+            map.insert(H64::hash(3), "three"),
+            Err(CapacityError::new((H64::hash(3), "three"))),
+        );
+        assert_eq!(map.entry(H64::hash(3)).or_insert("three"), Err(CapacityError::new("three")));
+        assert_eq!(map.remove(H64::hash(1)), Some("uno"));
+        assert_eq!(map.entry(H64::hash(3)).or_insert("three"), Ok(&mut "three"));
+        *map.entry(H64::hash(2)).and_modify(|v| *v = "dos").or_insert("two").unwrap() = "deux";
+        assert_eq!(map.get(H64::hash(2)), Some(&"deux"));
+        assert!(!map.contains_key(H64::hash(1)));
+        assert_eq!(map.iter().map(|(k, _)| k.unhash()).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(map.entry(H64::hash(2)).remove(), Some("deux"));
+        map.clear();
+        assert!(map.is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn small_map_spills()
+    {
+        let mut map = H64SmallMap::<u64, 4>::new();
+        for i in 0 .. 4 {
+            assert_eq!(map.insert(H64::hash(i), i), None);
+        }
+        assert!(!map.is_spilled());
+        assert_eq!(map.insert(H64::hash(4), 4), None);
+        assert!(map.is_spilled());
+        assert_eq!(map.len(), 5);
+        assert!((0 .. 5).all(|i| map.get(H64::hash(i)) == Some(&i)));
+        assert_eq!(map.remove(H64::hash(0)), Some(0));
+        assert_eq!(map.iter().map(|(_, v)| v).sum::<u64>(), 10);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn vec_map_stays_sorted()
+    {
+        let mut map: H64VecMap<u64> = test_values().map(|i| (H64::hash(i), i)).collect();
+        assert_eq!(map.insert(H64::hash(0), 1), Some(0));
+        assert_eq!(map.remove(H64::hash(1)), Some(1));
+        assert_eq!(map.insert(H64::hash(1), 1), None);
+        assert!(map.as_slice().windows(2).all(|w| w[0].0.into_raw() < w[1].0.into_raw()));
+        assert!(test_values().all(|i| map.contains_key(H64::hash(i))));
+
+        let half = u64::MAX / 2;
+        let low = map.range(.. half).count();
+        let high = map.range(half ..).count();
+        assert_eq!(low + high, map.len());
+        assert!(map.range(half ..).all(|(k, _)| k.into_raw() >= half));
+
+        let map: H64VecMap<&str> = [(H64::hash(1), "one"), (H64::hash(1), "uno")].into_iter().collect();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(H64::hash(1)), Some(&"uno"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn frozen_map_finds_all_keys()
+    {
+        for n in [0, 1, 2, 10, 2000] {
+            let map: H64FrozenMap<u64> = test_values().take(n).map(|i| (H64::hash(i), i)).collect();
+            assert_eq!(map.len(), n);
+            assert!(test_values().take(n).all(|i| map.get(H64::hash(i)) == Some(&i)));
+            assert!(test_values().skip(n).all(|i| !map.contains_key(H64::hash(i))));
+        }
+
+        let map = H64FrozenMap::new(vec![(H64::hash(1), "one"), (H64::hash(1), "uno")]);
+        assert_eq!(map.get(H64::hash(1)), Some(&"uno"));
+    }
+
+    #[test]
+    fn static_map_finds_all_keys()
+    {
+        static MAP: H64StaticMap<&str> = h64_static_map! {
+            0 => "zero",
+            1 => "one",
+            2 => "two",
+            3 => "three",
+            4 => "four",
+            5 => "five",
+            6 => "six",
+            u64::MAX => "max",
+        };
+        assert_eq!(MAP.len(), 8);
+        assert_eq!(MAP.get(H64::hash(0)), Some(&"zero"));
+        assert_eq!(MAP.get(H64::hash(6)), Some(&"six"));
+        assert_eq!(MAP.get(H64::hash(u64::MAX)), Some(&"max"));
+        assert!(!MAP.contains_key(H64::hash(7)));
+        assert_eq!(MAP.iter().count(), 8);
+
+        let map = h64_static_map! { 42 => 4.2 };
+        assert_eq!(map.get(H64::hash(42)), Some(&4.2));
+        let map: H64StaticMap<()> = h64_static_map! { };
+        assert_eq!(map.get(H64::hash(0)), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn mmap_map_reads_written_entries()
+    {
+        let mut bytes = Vec::new();
+        let entries = test_values().map(|i| (H64::hash(i), i.to_string()));
+        H64MmapMap::write(&mut bytes, entries).unwrap();
+        let map = H64MmapMap::new(&bytes).unwrap();
+        map.validate().unwrap();
+        assert_eq!(map.len(), 2000);
+        assert!(test_values().all(|i| map.get(H64::hash(i)) == Some(i.to_string().as_bytes())));
+        assert!(!map.contains_key(H64::hash(1000)));
+        assert_eq!(map.iter().count(), 2000);
+
+        assert!(H64MmapMap::new(&bytes[.. bytes.len() - 1]).is_err());
+        assert!(H64MmapMap::new(&bytes[.. 15]).is_err());
+        bytes[24 .. 32].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(H64MmapMap::new(&bytes).unwrap().validate(), Err(H64MmapError("keys not sorted")));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn dense_map_indexes_by_original()
+    {
+        let mut allocator = H64Allocator::new();
+        let mut map: H64DenseMap<u64> = (0 .. 10).map(|i| (allocator.alloc(), i)).collect();
+        assert_eq!(map.len(), 10);
+        assert_eq!(map.get(H64::hash(3)), Some(&3));
+        assert_eq!(map.insert(H64::hash(3), 30), Some(3));
+        assert_eq!(map.remove(H64::hash(4)), Some(4));
+        assert_eq!(map.remove(H64::hash(4)), None);
+        assert!(!map.contains_key(H64::hash(100)));
+        assert_eq!(map.iter().nth(3), Some((H64::hash(3), &30)));
+        assert_eq!(map.iter().next_back(), Some((H64::hash(9), &9)));
+        assert_eq!(map.len(), 9);
+
+        let mut grown = H64DenseMap::new();
+        grown.insert(H64::hash(5), ());
+        grown.remove(H64::hash(5));
+        assert_eq!(grown, H64DenseMap::new());
+        grown.insert(H64::hash(1), ());
+        assert_ne!(grown, H64DenseMap::new());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn bit_set_operations()
+    {
+        let evens: H64BitSet = (0 .. 200).step_by(2).map(H64::hash).collect();
+        let small: H64BitSet = (0 .. 100).map(H64::hash).collect();
+        assert_eq!(evens.len(), 100);
+        assert!(evens.contains(H64::hash(198)));
+        assert!(!evens.contains(H64::hash(199)));
+        assert!(!evens.contains(H64::hash(u64::MAX)));
+
+        let mut union = evens.clone();
+        union.union_with(&small);
+        assert_eq!(union.len(), 150);
+        let mut intersection = evens.clone();
+        intersection.intersect_with(&small);
+        assert!(Iterator::eq(intersection.iter(), (0 .. 100).step_by(2).map(H64::hash)));
+        assert!(intersection.is_subset(&evens) && intersection.is_subset(&small));
+        let mut difference = evens.clone();
+        difference.difference_with(&small);
+        assert!(Iterator::eq(difference.iter(), (100 .. 200).step_by(2).map(H64::hash)));
+
+        let mut set = H64BitSet::new();
+        assert!(set.insert(H64::hash(1000)));
+        assert!(!set.insert(H64::hash(1000)));
+        assert!(set.remove(H64::hash(1000)));
+        assert_eq!(set, H64BitSet::new());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn by_value_orders_by_original()
+    {
+        let mut map = H64BTreeMap::new();
+        for i in test_values().collect::<Vec<_>>().into_iter().rev() {
+            map.insert(OrdByValue(H64::hash(i)), i);
+        }
+        assert!(map.iter().map(|(k, &v)| (k.0.unhash(), v)).eq(test_values().map(|i| (i, i))));
+        let set: H64BTreeSet = test_values().map(|i| H64::hash(i).into()).collect();
+        assert!(set.into_iter().map(H64::from).eq(test_values().map(H64::hash)));
+    }
+
+    #[test]
+    fn by_hash_orders_by_raw()
+    {
+        let mut h64s: Vec<_> = test_values().map(|i| OrdByHash(H64::hash(i))).collect();
+        h64s.sort();
+        assert!(h64s.windows(2).all(|w| w[0].0.into_raw() < w[1].0.into_raw()));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sorted_iter_orders_by_original()
+    {
+        let map: H64HashMap<H64, u64> = test_values().map(|i| (H64::hash(i), i)).collect();
+        assert!(sorted_iter(&map).map(|(k, &v)| (k.unhash(), v)).eq(test_values().map(|i| (i, i))));
+        let set: H64HashSet<H64> = test_values().map(H64::hash).collect();
+        assert!(sorted_keys(&set).eq(test_values().map(H64::hash)));
+    }
+
+    #[test]
+    fn h64_range_is_exact()
+    {
+        let range = h64_range(10 .. 1000);
+        assert_eq!(range.len(), 990);
+        assert!(range.clone().eq((10 .. 1000).map(H64::hash)));
+        assert!(range.rev().eq((10 .. 1000).rev().map(H64::hash)));
+    }
+
+    #[test]
+    fn next_and_prev()
+    {
+        for i in test_values() {
+            assert_eq!(H64::hash(i).next(), i.checked_add(1).map(H64::hash));
+            assert_eq!(H64::hash(i).prev(), i.checked_sub(1).map(H64::hash));
+        }
+    }
+
+    #[test]
+    fn arithmetic_on_original()
+    {
+        for i in test_values() {
+            let h64 = H64::hash(i);
+            assert_eq!(h64.checked_add(500), i.checked_add(500).map(H64::hash));
+            assert_eq!(h64.checked_sub(500), i.checked_sub(500).map(H64::hash));
+            assert_eq!(h64.wrapping_add(500), H64::hash(i.wrapping_add(500)));
+        }
+    }
+
+    #[test]
+    fn pack_roundtrips()
+    {
+        for (hi, lo) in [(0, 0), (1, 2), (u32::MAX, 0), (0, u32::MAX), (u32::MAX, u32::MAX)] {
+            let h64 = H64::pack(hi, lo);
+            assert_eq!(h64.unhash(), (hi as u64) << 32 | lo as u64);
+            assert_eq!((h64.hi(), h64.lo()), (hi, lo));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn entity_id_is_newer_than()
+    {
+        let mut allocator = EntityAllocator::new();
+        let a: EntityId = allocator.alloc();
+        let b = allocator.alloc();
+        allocator.free(a);
+        let c = allocator.alloc();
+        assert_eq!((c.index(), c.generation()), (a.index(), 1));
+        assert!(c.is_newer_than(a));
+        assert!(!a.is_newer_than(c));
+        assert!(!b.is_newer_than(a));
+        assert!(!a.is_newer_than(a));
+    }
+
+    #[test]
+    fn tagged_h64_roundtrips()
+    {
+        type T = TaggedH64<4>;
+        assert_eq!((T::TAG_MAX, T::VALUE_MAX), (15, u64::MAX >> 4));
+        let tagged = T::new(9, 1234);
+        assert_eq!((tagged.tag(), tagged.value()), (9, 1234));
+        assert_eq!(tagged.get().unhash(), 9 << 60 | 1234);
+        assert_eq!(T::try_new(16, 0), None);
+        assert_eq!(T::try_new(0, T::VALUE_MAX + 1), None);
+
+        let mut a = TaggedH64Allocator::<4>::new(1);
+        let mut b = TaggedH64Allocator::<4>::new(2);
+        let (x, y) = (a.alloc(), b.alloc());
+        assert_eq!((x.tag(), x.value(), y.tag(), y.value()), (1, 0, 2, 0));
+        assert_ne!(x, y);
+
+        let mut allocator = TaggedH64Allocator::<4> { tag: 15, next: T::VALUE_MAX };
+        assert_eq!(allocator.try_alloc(), Some(T::new(15, T::VALUE_MAX)));
+        assert!(allocator.is_exhausted());
+        assert_eq!(allocator.try_alloc(), None);
+    }
+
+    #[test]
+    fn checked_rejects_typos()
+    {
+        let checked = H64Checked::new(123456789);
+        assert_eq!(checked.value(), 123456789);
+        let string = checked.to_string();
+        assert_eq!(string.parse(), Ok(checked));
+
+        let mut rejected = 0;
+        for i in 0 .. string.len() {
+            for digit in b'0' ..= b'9' {
+                let mut typo = string.clone().into_bytes();
+                if typo[i] == digit {
+                    continue;
+                }
+                typo[i] = digit;
+                let typo = String::from_utf8(typo).unwrap();
+                if let Err(err) = typo.parse::<H64Checked>() {
+                    assert_eq!(err.kind(), H64ParseErrorKind::Checksum);
+                    rejected += 1;
+                }
+            }
+        }
+        assert!(rejected >= 9 * string.len() * 9 / 10);
+
+        assert_eq!("".parse::<H64Checked>().unwrap_err().kind(), H64ParseErrorKind::Empty);
+        assert_eq!("12a".parse::<H64Checked>().unwrap_err().kind(), H64ParseErrorKind::InvalidDigit);
+        assert_eq!("99999999999999999999".parse::<H64Checked>().unwrap_err().kind(), H64ParseErrorKind::Overflow);
+        assert_eq!(H64Checked::try_new(H64Checked::VALUE_MAX + 1), None);
+    }
+
+    #[test]
+    fn from_str_roundtrips()
+    {
+        for i in test_values() {
+            assert_eq!(H64::hash(i).to_string().parse(), Ok(H64::hash(i)));
+        }
+        assert_eq!("-1".parse::<H64>().unwrap_err().kind(), H64ParseErrorKind::InvalidDigit);
+        assert_eq!("18446744073709551616".parse::<H64>().unwrap_err().kind(), H64ParseErrorKind::Overflow);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn base58_and_base62_roundtrip()
+    {
+        for i in test_values() {
+            let h64 = H64::hash(i);
+            assert_eq!(H64::from_base58(&h64.to_base58()), Ok(h64));
+            assert_eq!(H64::from_base62(&h64.to_base62()), Ok(h64));
+        }
+        assert_eq!(H64::hash(0).to_base58(), "1");
+        assert_eq!(H64::hash(57).to_base58(), "z");
+        assert_eq!(H64::hash(58).to_base58(), "21");
+        assert_eq!(H64::hash(u64::MAX).to_base62(), "LygHa16AHYF");
+        assert_eq!(H64::from_base58("0").unwrap_err().kind(), H64ParseErrorKind::InvalidDigit);
+        assert_eq!(H64::from_base58("").unwrap_err().kind(), H64ParseErrorKind::Empty);
+        assert_eq!(H64::from_base58("1"), Ok(H64::hash(0)));
+        assert_eq!(H64::from_base58("11").unwrap_err().kind(), H64ParseErrorKind::InvalidDigit);
+        assert_eq!(H64::from_base58("121").unwrap_err().kind(), H64ParseErrorKind::InvalidDigit);
+        assert_eq!(H64::from_base62("0"), Ok(H64::hash(0)));
+        assert_eq!(H64::from_base62("0042").unwrap_err().kind(), H64ParseErrorKind::InvalidDigit);
+        assert_eq!(H64::from_base62("LygHa16AHYG").unwrap_err().kind(), H64ParseErrorKind::Overflow);
+    }
+
+    #[test]
+    fn formats_in_other_radixes()
+    {
+        let h64 = H64::hash(0xBEEF);
+        assert_eq!(format!("{h64:x} {h64:#X} {h64:o} {h64:#b}"), "beef 0xBEEF 137357 0b1011111011101111");
+        assert_eq!(format!("{h64:08x}"), "0000beef");
+    }
+
+    #[test]
+    fn alternate_debug_shows_hashed()
+    {
+        let h64 = H64::hash(42);
+        assert_eq!(format!("{h64:?}"), "42");
+        assert_eq!(
+            format!("{h64:#?}"),
+            format!("H64 {{\n    value: 42,\n    hashed: {:#018x},\n}}", h64.into_raw()),
+        );
+    }
+
+    #[test]
+    fn redacted_hides_original()
+    {
+        let redacted = Redacted(H64::hash(42));
+        let string = redacted.to_string();
+        assert_eq!(string, format!("redacted-{:08x}", redacted.token()));
+        assert_eq!(format!("{redacted:?}"), format!("Redacted({string})"));
+        assert!(!string.contains("42"));
+        assert_ne!(Redacted(H64::hash(43)).token(), redacted.token());
+    }
+
+    #[test]
+    fn constants()
+    {
+        const IDS: [H64; 2] = [H64::ZERO; 2];
+        assert_eq!(IDS, [H64::hash(0), H64::default()]);
+        assert_eq!(H64::MAX.unhash(), u64::MAX);
+    }
+
+    #[test]
+    fn reserve_range_skips_reserved()
+    {
+        const NIL: H64 = H64::ZERO;
+        const SYSTEM: H64 = crate::h64!(1);
+        let mut allocator = H64Allocator::new();
+        allocator.reserve_range(0 .. 16);
+        allocator.reserve_range(20 .. 20);
+        assert_eq!(allocator.position(), 16);
+        allocator.reserve_range(16 .. 18);
+        assert_eq!(allocator.position(), 18);
+        let h64s: Vec<_> = allocator.by_ref().take(4).collect();
+        assert!(!h64s.contains(&NIL) && !h64s.contains(&SYSTEM));
+        assert_eq!(h64s[0], H64::hash(18));
+        assert_eq!(H64Allocator::starting_at(u64::MAX - 1).last(), Some(H64::hash(u64::MAX - 1)));
+    }
+
+    #[test]
+    #[should_panic = "reserved range overlaps allocated H64s"]
+    fn reserve_range_after_alloc_panics()
+    {
+        let mut allocator = H64Allocator::new();
+        allocator.alloc();
+        allocator.reserve_range(0 .. 16);
+    }
+
+    #[test]
+    #[should_panic = "reserved range overlaps allocated H64s"]
+    fn reserve_range_of_allocated_panics()
+    {
+        let mut allocator = H64Allocator::new();
+        allocator.alloc();
+        allocator.reserve_range(0 .. 1);
+    }
+
+    #[test]
+    fn namespaces_are_disjoint()
+    {
+        let mut users = H64Namespace::new(1);
+        let mut orders = H64Namespace::resume(2, 0);
+        let user = users.alloc();
+        let order = orders.alloc();
+        assert_eq!((user.namespace(), user.local()), (1, 0));
+        assert_eq!((order.namespace(), order.local()), (2, 0));
+        assert_ne!(user.get(), order.get());
+        assert!(users.contains(user.get()) && !users.contains(order.get()));
+        assert_eq!(users.alloc().get().unhash(), 1 << 56 | 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn interner_resolves_handles()
+    {
+        let mut interner: H64Interner = ["a", "b"].into_iter().collect();
+        let c = interner.intern("c");
+        assert_eq!(interner.intern("a"), H64::hash(0));
+        assert_eq!(c, H64::hash(2));
+        assert_eq!(interner.get("b"), Some(H64::hash(1)));
+        assert_eq!(interner.get("d"), None);
+        assert_eq!(interner.resolve(c), Some("c"));
+        assert_eq!(interner.resolve(H64::hash(3)), None);
+        assert_eq!(interner.len(), 3);
+        assert_eq!(format!("{interner:?}"), r#"{0: "a", 1: "b", 2: "c"}"#);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn interner_deduplicates_values()
+    {
+        let mut interner = Interner::<[u8]>::new();
+        let a = interner.intern(&b"abc"[..]);
+        let b = interner.intern(vec![1, 2, 3]);
+        assert_eq!(interner.intern(&[1, 2, 3][..]), b);
+        assert_eq!((a, b), (H64::hash(0), H64::hash(1)));
+        assert_eq!(interner.resolve(a), Some(&b"abc"[..]));
+
+        let interner: Interner<(u32, u32)> = [(1, 2), (3, 4), (1, 2)].into_iter().collect();
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.get(&(3, 4)), Some(H64::hash(1)));
+        assert_eq!(interner.resolve(H64::hash(0)), Some(&(1, 2)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn bimap_stays_one_to_one()
+    {
+        let mut map: H64BiMap<&str> = [(H64::hash(1), "a"), (H64::hash(2), "b")].into_iter().collect();
+        assert_eq!(map.get_by_key(H64::hash(1)), Some(&"a"));
+        assert_eq!(map.get_by_value(&"b"), Some(H64::hash(2)));
+        map.insert(H64::hash(1), "b");
+        assert_eq!(map.len(), 1);
+        assert!(!map.contains_value(&"a") && !map.contains_key(H64::hash(2)));
+        assert_eq!(map.remove_by_value(&"b"), Some(H64::hash(1)));
+        assert!(map.is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn multimap_groups_values()
+    {
+        let (a, b) = (H64::hash(1), H64::hash(2));
+        let mut map: H64MultiMap<u32> = [(a, 1), (b, 2), (a, 3)].into_iter().collect();
+        assert_eq!(map.get(a), [1, 3]);
+        assert_eq!(map.get(H64::hash(3)), []);
+        assert!(map.remove_value(a, &1));
+        assert!(!map.remove_value(a, &1));
+        assert!(map.remove_value(b, &2));
+        assert!(!map.contains_key(b));
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(a, &[3][..])]);
+        assert_eq!(map.remove(a), [3]);
+        assert!(map.is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn counter_counts()
+    {
+        let mut counter: H64Counter = [1, 2, 2, 3, 3, 3].map(H64::hash).into_iter().collect();
+        assert_eq!(counter.increment(H64::hash(1)), 2);
+        assert_eq!(counter.count(H64::hash(4)), 0);
+        assert_eq!(counter.most_common(2), [(H64::hash(3), 3), (H64::hash(1), 2)]);
+        let other: H64Counter = [4, 1].map(H64::hash).into_iter().collect();
+        counter.merge(&other);
+        assert_eq!(counter.most_common(10), [(1, 3), (3, 3), (2, 2), (4, 1)].map(|(i, n)| (H64::hash(i), n)));
+        assert_eq!(counter.total(), 9);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn lru_cache_evicts_least_recent()
+    {
+        let evicted = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let on_evict = {
+            let evicted = evicted.clone();
+            move |key: H64, value| evicted.lock().unwrap().push((key.unhash(), value))
+        };
+        let mut cache = H64LruCache::with_on_evict(3, on_evict);
+        for i in 0 .. 3 {
+            assert_eq!(cache.put(H64::hash(i), i), None);
+        }
+        assert_eq!(cache.get(H64::hash(0)), Some(&0));
+        assert_eq!(cache.put(H64::hash(3), 3), None);
+        assert_eq!(cache.put(H64::hash(0), 10), Some(0));
+        assert_eq!(cache.peek(H64::hash(2)), Some(&2));
+        assert_eq!(cache.put(H64::hash(4), 4), None);
+        assert_eq!(*evicted.lock().unwrap(), [(1, 1), (2, 2)]);
+        assert_eq!(cache.remove(H64::hash(3)), Some(3));
+        assert!(cache.iter().map(|(k, &v)| (k.unhash(), v)).eq([(4, 4), (0, 10)]));
+        assert_eq!(cache.len(), 2);
+
+        let mut cache = H64LruCache::new(100);
+        for i in 0 .. 1000 {
+            cache.put(H64::hash(i % 150), i);
+            if i % 7 == 0 {
+                cache.remove(H64::hash(i * 3 % 150));
+            }
+        }
+        let keys: Vec<_> = cache.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys.len(), cache.len());
+        assert!(keys.iter().all(|&k| cache.contains_key(k)));
+        assert_eq!(cache.iter().map(|(_, &v)| v).next(), Some(999));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn ttl_cache_expires_entries()
+    {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut cache = H64TtlCache::new(Duration::from_secs(10));
+        cache.insert_at(H64::hash(1), "a", at(0));
+        cache.insert_at(H64::hash(2), "b", at(5));
+        assert_eq!(cache.get_at(H64::hash(1), at(9)), Some(&"a"));
+        assert_eq!(cache.get_at(H64::hash(1), at(10)), None);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains_key_at(H64::hash(2), at(14)));
+        assert_eq!(cache.insert_at(H64::hash(3), "c", at(15)), None);
+        assert_eq!(cache.purge_expired_at(at(15)), 1);
+        assert_eq!(cache.remove_at(H64::hash(3), at(16)), Some("c"));
+        assert!(cache.is_empty());
+
+        let mut cache = H64TtlCache::new(Duration::MAX);
+        cache.insert_at(H64::hash(1), "a", at(0));
+        assert_eq!(cache.purge_expired_at(at(1_000_000_000)), 0);
+        assert_eq!(cache.get_at(H64::hash(1), at(1_000_000_000)), Some(&"a"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn registry_keeps_handles_stable()
+    {
+        let mut registry = H64Registry::new();
+        let handles: Vec<_> = (0 .. 10).map(|i| registry.insert(i)).collect();
+        assert_eq!(registry.remove(handles[3]), Some(3));
+        assert_eq!(registry.remove(handles[3]), None);
+        assert_eq!(registry.remove(handles[9]), Some(9));
+        let new = registry.insert(10);
+        assert!(!handles.contains(&new));
+        *registry.get_mut(handles[0]).unwrap() += 100;
+        for (i, &handle) in handles.iter().enumerate() {
+            let expected = match i { 0 => Some(100), 3 | 9 => None, _ => Some(i) };
+            assert_eq!(registry.get(handle).copied(), expected);
+        }
+        assert_eq!(registry.len(), 9);
+        assert!(registry.iter().all(|(handle, value)| registry.get(handle) == Some(value)));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn versioned_registry_rejects_stale_handles()
+    {
+        let mut registry = H64VersionedRegistry::new();
+        let a = registry.insert("a");
+        let b = registry.insert("b");
+        assert_eq!(registry.remove(a), Some("a"));
+        let c = registry.insert("c");
+        assert_eq!(c.index(), a.index());
+        assert_eq!(registry.get(a), None);
+        assert_eq!(registry.remove(a), None);
+        assert_eq!(registry.get(c), Some(&"c"));
+        *registry.get_mut(b).unwrap() = "B";
+        assert!(registry.iter().eq([(c, &"c"), (b, &"B")]));
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn arena_indexes_by_handle()
+    {
+        let mut arena = H64Arena::with_base(100);
+        let a = arena.alloc("a");
+        let b = arena.alloc("b");
+        assert_eq!((a, b), (H64::hash(100), H64::hash(101)));
+        arena[b] = "B";
+        assert_eq!((arena[a], arena.get(b)), ("a", Some(&"B")));
+        assert_eq!(arena.get(H64::hash(99)), None);
+        assert_eq!(arena.get(H64::hash(102)), None);
+        assert!(arena.iter().eq([(a, &"a"), (b, &"B")]));
+        arena.clear();
+        assert_eq!(arena.get(a), None);
+        assert_eq!(arena.alloc("c"), a);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn graph_traversals()
+    {
+        let [a, b, c, d, e] = [1, 2, 3, 4, 5].map(H64::hash);
+        let mut graph = H64Graph::new();
+        graph.add_node(e);
+        for (from, to) in [(a, b), (a, c), (b, d), (c, d)] {
+            graph.add_edge(from, to);
+        }
+        assert_eq!((graph.node_count(), graph.edge_count()), (5, 4));
+        assert_eq!(graph.neighbors(a), [b, c]);
+        assert!(graph.contains_edge(c, d) && !graph.contains_edge(d, c));
+        assert_eq!(graph.bfs(a), [a, b, c, d]);
+        assert_eq!(graph.dfs(a), [a, b, d, c]);
+        assert_eq!(graph.bfs(H64::hash(6)), []);
+        assert_eq!(graph.topological_sort(), Some(vec![e, a, b, c, d]));
+        graph.add_edge(d, a);
+        assert_eq!(graph.topological_sort(), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn union_find_merges_sets()
+    {
+        let mut sets = H64UnionFind::new();
+        let h = H64::hash;
+        assert!(sets.union(h(1), h(2)));
+        assert!(sets.union(h(3), h(4)));
+        assert_eq!(sets.set_count(), 2);
+        assert!(sets.union(h(2), h(5)));
+        assert!(sets.union(h(4), h(1)));
+        assert!(!sets.union(h(5), h(3)));
+        assert_eq!(sets.set_count(), 1);
+        assert!(sets.same_set(h(3), h(5)));
+        assert!(!sets.same_set(h(1), h(6)));
+        assert_eq!(sets.find(h(6)), h(6));
+        for i in 7 .. 1000 {
+            sets.union(h(i - 1), h(i));
+        }
+        assert_eq!(sets.set_count(), 2);
+        assert_eq!(sets.find(h(7)), sets.find(h(999)));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn interval_map_splits_ranges()
+    {
+        let mut map = H64IntervalMap::new();
+        map.insert(0 .. 100, 'a');
+        map.insert(100 .. 200, 'b');
+        map.insert(50 .. 150, 'c');
+        map.insert(10 .. 20, 'd');
+        map.insert(5 .. 5, 'e');
+        let expected = [(0 .. 10, 'a'), (10 .. 20, 'd'), (20 .. 50, 'a'), (50 .. 150, 'c'), (150 .. 200, 'b')];
+        assert!(map.iter().map(|(r, &v)| (r, v)).eq(expected));
+        assert_eq!(map.get(H64::hash(49)), Some(&'a'));
+        assert_eq!(map.get(H64::hash(150)), Some(&'b'));
+        assert_eq!(map.get(H64::hash(200)), None);
+        assert!(map.overlapping(15 .. 51).map(|(_, &v)| v).eq(['d', 'a', 'c']));
+        assert_eq!(map.overlapping(20 .. 20).count(), 0);
+        map.remove(5 .. 160);
+        assert!(map.iter().eq([(0 .. 5, &'a'), (160 .. 200, &'b')]));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn priority_queue_changes_priorities()
+    {
+        let mut queue: H64PriorityQueue<u64> =
+            (0 .. 100).map(|i| (H64::hash(i), i * 7 % 100)).collect();
+        assert_eq!(queue.push(H64::hash(0), 1000), Some(0));
+        assert_eq!(queue.change_priority(H64::hash(1), 500), Some(7));
+        assert_eq!(queue.change_priority(H64::hash(100), 1), None);
+        assert_eq!(queue.remove(H64::hash(2)), Some(14));
+        assert_eq!(queue.peek(), Some((H64::hash(0), &1000)));
+        assert_eq!(queue.pop(), Some((H64::hash(0), 1000)));
+        assert_eq!(queue.pop(), Some((H64::hash(1), 500)));
+        assert_eq!(queue.priority(H64::hash(3)), Some(&21));
+        let priorities: Vec<_> = core::iter::from_fn(|| queue.pop()).map(|(_, p)| p).collect();
+        assert_eq!(priorities.len(), 97);
+        assert!(priorities.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn bloom_filter_has_no_false_negatives()
+    {
+        let mut filter = H64BloomFilter::with_rate(1000, 0.01);
+        assert_eq!((filter.num_bits(), filter.num_hashes()), (9586, 7));
+        assert!(filter.insert(H64::hash(0)));
+        assert!(!filter.insert(H64::hash(0)));
+        for i in 1 .. 1000 {
+            filter.insert(H64::hash(i));
+        }
+        assert!((0 .. 1000).all(|i| filter.contains(H64::hash(i))));
+        let false_positives = (1000 .. 11000).filter(|&i| filter.contains(H64::hash(i))).count();
+        assert!(false_positives < 200, "{false_positives}");
+
+        let mut other = H64BloomFilter::new(filter.num_bits(), filter.num_hashes());
+        other.insert(H64::hash(u64::MAX));
+        other.union_with(&filter);
+        assert!(other.contains(H64::hash(u64::MAX)) && other.contains(H64::hash(500)));
+        let words = other.words().to_vec();
+        assert_eq!(H64BloomFilter::from_words(other.num_bits(), other.num_hashes(), words), Some(other));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn cuckoo_filter_removes()
+    {
+        let mut filter = H64CuckooFilter::with_capacity(1000);
+        assert_eq!(filter.capacity(), 1024);
+        for i in 0 .. 950 {
+            filter.insert(H64::hash(i)).unwrap();
+        }
+        assert!((0 .. 950).all(|i| filter.contains(H64::hash(i))));
+        let false_positives = (1000 .. 101000).filter(|&i| filter.contains(H64::hash(i))).count();
+        assert!(false_positives < 30, "{false_positives}");
+        for i in 0 .. 500 {
+            assert!(filter.remove(H64::hash(i)));
+        }
+        assert_eq!(filter.len(), 450);
+        assert!((500 .. 950).all(|i| filter.contains(H64::hash(i))));
+        assert!((0 .. 500).filter(|&i| filter.contains(H64::hash(i))).count() < 5);
+
+        let mut filter = H64CuckooFilter::with_capacity(4);
+        let inserted = (0 .. 100).take_while(|&i| filter.insert(H64::hash(i)).is_ok()).count();
+        assert!((5 ..= 9).contains(&inserted), "{inserted}");
+        assert!((0 .. inserted as u64).all(|i| filter.contains(H64::hash(i))));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn count_min_sketch_never_undercounts()
+    {
+        let mut sketch = H64CountMinSketch::with_error(0.01, 0.01);
+        assert_eq!((sketch.width(), sketch.depth()), (272, 5));
+        for i in 0 .. 1000 {
+            sketch.add(H64::hash(i), i % 10);
+        }
+        let mut other = H64CountMinSketch::new(sketch.width(), sketch.depth());
+        other.increment(H64::hash(7));
+        sketch.merge(&other);
+        for i in 0 .. 1000 {
+            let (actual, estimate) = (i % 10 + u64::from(i == 7), sketch.estimate(H64::hash(i)));
+            assert!(actual <= estimate && estimate <= actual + 45, "{i}: {estimate}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hyperloglog_estimates_cardinality()
+    {
+        let mut hll = H64HyperLogLog::new(12);
+        hll.extend((0 .. 100).map(H64::hash));
+        assert!((hll.estimate() - 100.0).abs() < 5.0, "{}", hll.estimate());
+        let mut other = H64HyperLogLog::new(12);
+        other.extend((50 .. 100_000).map(H64::hash));
+        hll.merge(&other);
+        assert!((hll.estimate() / 100_000.0 - 1.0).abs() < 0.05, "{}", hll.estimate());
+        let copy = H64HyperLogLog::from_registers(12, hll.registers().to_vec());
+        assert_eq!(copy, Some(hll));
+        assert_eq!(H64HyperLogLog::from_registers(12, vec![0; 100]), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn top_k_tracks_heavy_hitters()
+    {
+        let mut top_k = H64TopK::new(8);
+        for i in 0 .. 1000 {
+            top_k.offer(H64::hash(i % 3));
+            top_k.offer(H64::hash(1000 + i));
+        }
+        assert_eq!(top_k.len(), 8);
+        assert_eq!(top_k.total(), 2000);
+        assert_eq!(top_k.max_error(), 250);
+        let top = top_k.top(3);
+        let keys: Vec<_> = top.iter().map(|&(key, _, _)| key.unhash()).collect();
+        assert_eq!(keys, [0, 1, 2]);
+        for &(key, count, error) in &top {
+            assert!(count - error <= 334 && 333 <= count, "{key:?}");
+        }
+    }
+
+    #[test]
+    fn shard_moves_few_ids()
+    {
+        for i in test_values() {
+            assert_eq!(H64::hash(i).shard(1), 0);
+        }
+        let ids: Vec<_> = (0 .. 10_000).map(H64::hash).collect();
+        let mut counts = [0; 10];
+        for &id in &ids {
+            let before = id.shard(10);
+            let after = id.shard(11);
+            counts[before as usize] += 1;
+            assert!(after == before || after == 10);
+        }
+        assert!(counts.iter().all(|&count| (800 .. 1200).contains(&count)), "{counts:?}");
+        let moved = ids.iter().filter(|id| id.shard(11) == 10).count();
+        assert!((700 .. 1100).contains(&moved), "{moved}");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn rendezvous_moves_only_removed_node_keys()
+    {
+        let nodes: Vec<_> = (0 .. 5).map(H64::hash).collect();
+        assert_eq!(rendezvous::select(H64::hash(0), &[]), None);
+        let mut counts = [0; 5];
+        for key in (0 .. 5000).map(H64::hash) {
+            let top = rendezvous::top_k(key, &nodes, 3);
+            assert_eq!(top.len(), 3);
+            assert_eq!(Some(top[0]), rendezvous::select(key, &nodes));
+            counts[top[0].unhash() as usize] += 1;
+            let remaining: Vec<_> = nodes.iter().copied().filter(|&node| node != top[0]).collect();
+            assert_eq!(rendezvous::top_k(key, &remaining, 2), top[1 ..]);
+        }
+        assert!(counts.iter().all(|&count| (800 .. 1200).contains(&count)), "{counts:?}");
+    }
+
+    #[test]
+    fn partition_is_uniform()
+    {
+        let h64s: Vec<_> = (0 .. 10_000).map(H64::hash).collect();
+        let mut buckets = vec![0; h64s.len()];
+        H64::partition_slice(&h64s, 7, &mut buckets);
+        let mut counts = [0; 7];
+        for (h64, &bucket) in Iterator::zip(h64s.iter(), buckets.iter()) {
+            assert_eq!(h64.partition(7), bucket);
+            counts[bucket] += 1;
+        }
+        assert!(counts.iter().all(|&count| (1300 .. 1560).contains(&count)), "{counts:?}");
+        assert_eq!(H64::MAX.partition(1), 0);
+        assert_eq!(H64(u64::MAX).partition(usize::MAX), usize::MAX - 1);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn unique_h64_skips_duplicates()
+    {
+        let h64s = || (0 .. 3000).map(|i| H64::hash(i % 1000));
+        let unique: Vec<_> = h64s().unique_h64_with_capacity(1000).collect();
+        assert_eq!(h64s().unique_h64().collect::<Vec<_>>(), unique);
+        assert_eq!(unique, (0 .. 1000).map(H64::hash).collect::<Vec<_>>());
+        let approx: Vec<_> = h64s().unique_h64_approx(H64BloomFilter::new(16384, 7)).collect();
+        assert!(approx.len() <= 1000 && approx.len() > 990, "{}", approx.len());
+        assert_eq!(approx.iter().copied().unique_h64().count(), approx.len());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn iterator_ext_collects_into_h64_collections()
+    {
+        let map = (0 .. 10).map(|i| (H64::hash(i % 5), i)).collect_h64_map();
+        assert_eq!(map.len(), 5);
+        assert_eq!(map[&H64::hash(2)], 7);
+        let set = (0 .. 10).map(|i| H64::hash(i % 5)).collect_h64_set();
+        let keys: Vec<_> = sorted_keys(&set).collect();
+        assert_eq!(keys, (0 .. 5).map(H64::hash).collect::<Vec<_>>());
+        let groups = (0 .. 10u64).group_by_h64(|&i| H64::hash(i % 3));
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[&H64::hash(0)], [0, 3, 6, 9]);
+        assert_eq!(groups[&H64::hash(2)], [2, 5, 8]);
+    }
+
+    #[test]
+    fn hasher_does_nothing()
+    {
+        for i in test_values() {
+            let h64 = H64::hash(i);
+            let mut hasher = H64Hasher::default();
+            Hash::hash(&h64, &mut hasher);
+            assert_eq!(hasher.finish(), h64.0);
+        }
+    }
+
+    #[test]
+    fn hashers_32_128_do_little()
+    {
+        for i in test_values() {
+            let h32 = H32::hash(i as u32);
+            let mut hasher = H32Hasher::default();
+            Hash::hash(&h32, &mut hasher);
+            assert_eq!(hasher.finish(), u64::from(h32.0) << 32 | u64::from(h32.0));
+
+            let h128 = H128::hash(u128::from(i));
+            let mut hasher = H128Hasher::default();
+            Hash::hash(&h128, &mut hasher);
+            assert_eq!(hasher.finish(), h128.0 as u64);
         }
     }
 }